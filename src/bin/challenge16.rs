@@ -1,8 +1,11 @@
-use cryptopals::utils::aes::{
-  aes::AES,
-  aes_error::AESError,
-  constants::AES_BLOCK_SIZE,
-  utils::{pkcs_padding, AESMode},
+use cryptopals::utils::{
+  aes::{
+    aes::AES,
+    aes_error::AESError,
+    constants::AES_BLOCK_SIZE,
+    utils::AESMode,
+  },
+  conversion::conversion::find_subsequence,
 };
 use rand::{thread_rng, Rng};
 
@@ -22,8 +25,7 @@ fn modify_and_encrypt_string<S: AsRef<[u8]>>(
     b";comment2=%20like%20a%20pound%20of%20bacon".to_vec(),
   ]
   .concat();
-  let padded_plaintext = pkcs_padding(&plaintext_bytes, AES_BLOCK_SIZE as u8);
-  AES::encode(&padded_plaintext, &key, AESMode::CBC([0; 16]))
+  AES::encode(&plaintext_bytes, &key, AESMode::CBC([0; 16]))
 }
 
 fn decrypt_and_look_for_admin_true<S: AsRef<[u8]>>(
@@ -32,10 +34,7 @@ fn decrypt_and_look_for_admin_true<S: AsRef<[u8]>>(
 ) -> Result<bool, AESError> {
   let plaintext_bytes = AES::decode(ciphertext, &key, AESMode::CBC([0; 16]))?;
   let target: &[u8; 12] = b";admin=true;";
-  let result = plaintext_bytes
-    .windows(target.len())
-    .any(|window| window == target);
-  Ok(result)
+  Ok(find_subsequence(&plaintext_bytes, target).is_some())
 }
 
 fn main() -> Result<(), AESError> {
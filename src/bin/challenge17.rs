@@ -1,8 +1,11 @@
-use cryptopals::utils::aes::{
-  aes::AES,
-  aes_error::AESError,
-  constants::AES_BLOCK_SIZE,
-  utils::{has_valid_pkcs_padding, pkcs_padding, AESMode},
+use cryptopals::utils::{
+  aes::{
+    aes::AES,
+    aes_error::AESError,
+    constants::AES_BLOCK_SIZE,
+    utils::{has_valid_pkcs_padding, strip_pkcs_padding, AESMode},
+  },
+  conversion::conversion::into_fixed_blocks,
 };
 use rand::{thread_rng, Rng};
 
@@ -67,13 +70,12 @@ impl PaddingOracle {
 
   fn select_random_and_encrypt(&self) -> Result<(Vec<u8>, [u8; 16]), AESError> {
     let random_index: usize = thread_rng().gen_range(0..10);
-    let plaintext = pkcs_padding(&STRINGS[random_index], AES_BLOCK_SIZE as u8);
-    let ciphertext = AES::encode(&plaintext, &self.key, AESMode::CBC(self.encr_iv))?;
+    let ciphertext = AES::encode(&STRINGS[random_index], &self.key, AESMode::CBC(self.encr_iv))?;
     Ok((ciphertext, self.encr_iv))
   }
 
   fn check_padding<S: AsRef<[u8]>>(&self, ciphertext: &S, iv: &[u8; 16]) -> Result<bool, AESError> {
-    let plaintext = AES::decode(ciphertext, &self.key, AESMode::CBC(iv.clone()))?;
+    let plaintext = AES::decode_no_unpad(ciphertext, &self.key, AESMode::CBC(iv.clone()))?;
     Ok(has_valid_pkcs_padding(&plaintext, AES_BLOCK_SIZE as u8).is_ok())
   }
 }
@@ -119,14 +121,7 @@ fn main() -> Result<(), AESError> {
   assert_eq!(iv.len() % BLOCK_SIZE, 0);
 
   let mut plaintext: Vec<u8> = vec![];
-  let cipherblocks: Vec<[u8; 16]> = ciphertext
-    .chunks(BLOCK_SIZE)
-    .map(|chunk| {
-      let mut arr = [0u8; 16];
-      arr[0..BLOCK_SIZE].copy_from_slice(chunk);
-      arr
-    })
-    .collect();
+  let cipherblocks: Vec<[u8; 16]> = into_fixed_blocks(&ciphertext)?;
 
   for cipherblock in cipherblocks {
     let decrypted = single_block_poa(&cipherblock, &oracle)?;
@@ -138,6 +133,7 @@ fn main() -> Result<(), AESError> {
     plaintext.extend(plainblock);
     iv = cipherblock;
   }
+  let plaintext = strip_pkcs_padding(&plaintext, AES_BLOCK_SIZE as u8)?;
   dbg!(&plaintext, String::from_utf8(plaintext.clone()).unwrap());
 
   Ok(())
@@ -1,8 +1,11 @@
-use cryptopals::utils::aes::{
-  aes::AES,
-  aes_error::AESError,
-  constants::AES_BLOCK_SIZE,
-  utils::{pkcs_padding, AESMode},
+use cryptopals::utils::{
+  aes::{
+    aes::AES,
+    aes_error::AESError,
+    constants::AES_BLOCK_SIZE,
+    utils::AESMode,
+  },
+  conversion::conversion::find_subsequence,
 };
 use rand::{thread_rng, Rng};
 
@@ -32,8 +35,7 @@ impl AttackerAPI {
       b";comment2=%20like%20a%20pound%20of%20bacon".to_vec(),
     ]
     .concat();
-    let padded_plaintext = pkcs_padding(&plaintext_bytes, AES_BLOCK_SIZE as u8);
-    AES::encode(&padded_plaintext, &self.key, AESMode::CTR(self.nonce))
+    AES::encode(&plaintext_bytes, &self.key, AESMode::CTR(self.nonce))
   }
 
   fn decrypt_and_look_for_admin_true<S: AsRef<[u8]>>(
@@ -42,10 +44,7 @@ impl AttackerAPI {
   ) -> Result<bool, AESError> {
     let plaintext_bytes = AES::decode(ciphertext, &self.key, AESMode::CTR(self.nonce))?;
     let target: &[u8; 12] = b";admin=true;";
-    let result = plaintext_bytes
-      .windows(target.len())
-      .any(|window| window == target);
-    Ok(result)
+    Ok(find_subsequence(&plaintext_bytes, target).is_some())
   }
 }
 
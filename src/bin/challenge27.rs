@@ -1,8 +1,7 @@
 use cryptopals::utils::aes::{
   aes::AES,
   aes_error::AESError,
-  constants::AES_BLOCK_SIZE,
-  utils::{pkcs_padding, AESMode},
+  utils::AESMode,
 };
 use rand::{thread_rng, Rng};
 
@@ -28,12 +27,12 @@ impl AttackerAPI {
       b";comment2=%20like%20a%20pound%20of%20bacon".to_vec(),
     ]
     .concat();
-    let padded_plaintext = pkcs_padding(&plaintext_bytes, AES_BLOCK_SIZE as u8);
-    AES::encode(&padded_plaintext, &self.key, AESMode::CBC(self.key))
+    AES::encode(&plaintext_bytes, &self.key, AESMode::CBC(self.key))
   }
 
   fn decrypt_and_check_ascii<S: AsRef<[u8]>>(&self, ciphertext: &S) -> Result<Vec<u8>, AESError> {
-    let plaintext = AES::decode(ciphertext, &self.key, AESMode::CBC(self.key))?;
+    // c1 0 c1 does not end in a valid padding, we want to read all of it anyway
+    let plaintext = AES::decode_no_unpad(ciphertext, &self.key, AESMode::CBC(self.key))?;
     if plaintext.iter().any(|&byte| byte > b'z') {
       //return Err(AESError::AsciiError(plaintext));
       // We assume we get the error and we as humans read it
@@ -57,7 +57,7 @@ impl Server {
   // Only for testing purposes
   fn get_signature_for(&self, file: String) -> HexString {
     let digest: Sha1Digest = self.hash.authenticate(&file);
-    HexString::try_from(digest.to_vec()).unwrap()
+    HexString::from_array(&digest)
   }
 
   // timing leak
@@ -94,11 +94,9 @@ impl Server {
     let params = captures.unwrap();
     let file = params[1].to_string();
     let signature_str = params[2].to_string();
-    let signature = HexString::try_from(signature_str)
-      .map_err(|_| BadRequestError)?
-      .as_vector_of_bytes()
-      .map_err(|_| BadRequestError)?;
-    assert_eq!(signature.len(), 20);
+    let signature_hex = HexString::try_from(signature_str).map_err(|_| BadRequestError)?;
+    assert_eq!(signature_hex.len_bytes(), 20);
+    let signature = signature_hex.as_vector_of_bytes().map_err(|_| BadRequestError)?;
     Ok(RequestParams {
       file,
       signature: signature.try_into().unwrap(),
@@ -131,7 +129,7 @@ fn main() -> Result<(), SystemTimeError> {
     let (mut max_byte, mut max_duration): (u8, Duration) = (0, Duration::new(0, 0));
     for byte in 0u8..=255 {
       obtained_signature[i] = byte;
-      let hex = HexString::try_from(obtained_signature.to_vec()).unwrap();
+      let hex = HexString::from_array(&obtained_signature);
       let args = format!("?file={}&signature={}", file, hex);
       /*let params = RequestParams {
         file: file.clone(),
@@ -150,7 +148,7 @@ fn main() -> Result<(), SystemTimeError> {
   }
 
   // Verify it was correct!
-  let hex = HexString::try_from(obtained_signature.to_vec()).unwrap();
+  let hex = HexString::from_array(&obtained_signature);
   let args = format!("?file={}&signature={}", file, hex);
   assert_eq!(server.process_request(args), HTTPResponseCode::Success);
 
@@ -1,13 +1,11 @@
 use cryptopals::utils::{
+  aes::aes_error::AESError,
   algebra::primes::get_nist_prime,
-  aes::{aes::AES, aes_error::AESError, utils::AESMode},
-  dh::{DiffieHellmanParty, DiffieHellmanSession},
-  mac::sha1::Sha1,
+  dh::{simulate_dh_mitm, DiffieHellmanParty, DiffieHellmanSession, GTampering},
 };
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 use rand::{thread_rng, Rng};
-use sha2::{Digest, Sha256};
 
 fn dh_with_ack() -> Result<(), AESError> {
   let (p, g) = (get_nist_prime(), BigUint::from(2u32));
@@ -25,20 +23,13 @@ fn dh_with_ack() -> Result<(), AESError> {
   assert_eq!(a_session, b_session);
 
   // Alice sends AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv to Bob
-  let a_key_bytes: [u8; 16] = Sha1::hash(&a_session.encryption_key)[..16]
-    .try_into()
-    .unwrap();
   let random_iv: [u8; 16] = thread_rng().gen();
   let message: [u8; 16] = thread_rng().gen();
-  let ciphertext = AES::encode(&message, &a_key_bytes, AESMode::CBC(random_iv))?;
+  let ciphertext = a_session.encrypt_message(&message, &random_iv);
   // Now Bob has (ciphertext, random_iv)
 
   // Bob decrypts Alice's message and is able to send it back to Alice
-  let b_key_bytes: [u8; 16] = Sha1::hash(&a_session.encryption_key)[..16]
-    .try_into()
-    .unwrap();
-  assert_eq!(a_key_bytes, b_key_bytes);
-  let alice_message = AES::decode(&ciphertext, &b_key_bytes, AESMode::CBC(random_iv))?;
+  let alice_message = b_session.decrypt_message(&ciphertext, &random_iv)?;
   assert_eq!(message.to_vec(), alice_message);
 
   Ok(())
@@ -62,13 +53,9 @@ fn mitm_attack_with_g_one() -> Result<(), AESError> {
   // Alice sends AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv to Bob
   // If M intercepts the attack, it is s = B^a = 1^a = 1.
   // So M is able to intercept and decode Alice's messages (and Bob is not).
-  
-  let a_key_bytes: [u8; 16] = Sha1::hash(&a_session.encryption_key)[..16]
-    .try_into()
-    .unwrap();
   let random_iv: [u8; 16] = thread_rng().gen();
   let message: [u8; 16] = thread_rng().gen();
-  let ciphertext = AES::encode(&message, &a_key_bytes, AESMode::CBC(random_iv))?;
+  let ciphertext = a_session.encrypt_message(&message, &random_iv);
   
   // M decodes the message
   let m_message = decode_message_with(&ciphertext, BigUint::one(), &random_iv)?;
@@ -94,12 +81,9 @@ fn mitm_attack_with_g_p() -> Result<(), AESError> {
   // Alice sends AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv to Bob
   // If M intercepts the attack, it is s = 0^a = 0
   // So M is able to intercept and decode Alice's messages (and Bob is not).
-  let a_key_bytes: [u8; 16] = Sha1::hash(&a_session.encryption_key)[..16]
-    .try_into()
-    .unwrap();
   let random_iv: [u8; 16] = thread_rng().gen();
   let message: [u8; 16] = thread_rng().gen();
-  let ciphertext = AES::encode(&message, &a_key_bytes, AESMode::CBC(random_iv))?;
+  let ciphertext = a_session.encrypt_message(&message, &random_iv);
   
   // M decodes the message
   let m_message = decode_message_with(&ciphertext, BigUint::zero(), &random_iv)?;
@@ -125,31 +109,21 @@ fn mitm_attack_with_g_p_minus_one() -> Result<(), AESError> {
   // Alice sends AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv to Bob
   // If M intercepts the attack, it is s = 1 or p-1
   // So M is able to intercept and decode Alice's messages (and Bob is not).
-  let a_key_bytes: [u8; 16] = Sha1::hash(&a_session.encryption_key)[..16]
-    .try_into()
-    .unwrap();
   let random_iv: [u8; 16] = thread_rng().gen();
   let message: [u8; 16] = thread_rng().gen();
-  let ciphertext = AES::encode(&message, &a_key_bytes, AESMode::CBC(random_iv))?;
+  let ciphertext = a_session.encrypt_message(&message, &random_iv);
   
   // M decodes the message
-  let m1 = decode_message_with(&ciphertext, BigUint::one(), &random_iv)?; // try with s = 1
-  let m2 = decode_message_with(&ciphertext, p - BigUint::one(), &random_iv)?; // try with s = p - 1
-  assert!(message.to_vec() == m1 || message.to_vec() == m2);
+  // The wrong guess almost surely fails the padding check
+  let m1 = decode_message_with(&ciphertext, BigUint::one(), &random_iv).ok(); // try with s = 1
+  let m2 = decode_message_with(&ciphertext, p - BigUint::one(), &random_iv).ok(); // try with s = p - 1
+  assert!(m1 == Some(message.to_vec()) || m2 == Some(message.to_vec()));
   
   Ok(())
 }
 
 fn decode_message_with(ciphertext: &Vec<u8>, s: BigUint, iv: &[u8; 16]) -> Result<Vec<u8>, AESError> { 
-  let mut hasher = Sha256::new();
-  hasher.update(s.to_bytes_be());
-  let digest = hasher.finalize();
-  let m_session = DiffieHellmanSession {
-    encryption_key: digest[..16].try_into().unwrap(),
-    mac_key: digest[16..32].try_into().unwrap(),
-  };
-  let m_key_bytes: [u8; 16] = Sha1::hash(&m_session.encryption_key)[..16].try_into().unwrap();
-  AES::decode(&ciphertext, &m_key_bytes, AESMode::CBC(iv.clone()))
+  DiffieHellmanSession::from_shared_secret(&s).decrypt_message(ciphertext, iv)
 }
 
 // In addition, A = g^a then because of DLP, M will not be able to decrypt Bob's messages
@@ -158,5 +132,11 @@ fn main() -> Result<(), AESError> {
   mitm_attack_with_g_one()?;
   mitm_attack_with_g_p()?;
   mitm_attack_with_g_p_minus_one()?;
+
+  // The same attacks end to end. A hardened Bob would have refused every generator injected above
+  for attack in [GTampering::One, GTampering::P, GTampering::PMinusOne] {
+    let result = simulate_dh_mitm(attack);
+    assert!(result.attacker_decrypted() && result.hardened_abort);
+  }
   Ok(())
 }
@@ -3,7 +3,7 @@ use std::io::{BufRead, BufReader};
 
 use cryptopals::utils::conversion::conversion::ConversionError;
 use cryptopals::utils::conversion::hex_string::HexString;
-use cryptopals::utils::conversion::print::xor_against_all_bytes;
+use cryptopals::utils::metrics::find_xored_line;
 
 fn main() -> Result<(), ConversionError> {
   const PATH: &str = "./src/data/1-4.txt";
@@ -12,12 +12,14 @@ fn main() -> Result<(), ConversionError> {
   // BufReader allows to handle the file efficiently
   let reader = BufReader::new(file);
 
+  let mut candidates: Vec<Vec<u8>> = Vec::new();
   for line in reader.lines() {
-    let line = HexString::try_from(line.unwrap())?;
-    //println!("{}", line);
-    xor_against_all_bytes(line, 0.4)?;
+    candidates.push(HexString::try_from(line.unwrap())?.as_vector_of_bytes()?);
   }
 
+  let (index, key, plaintext) = find_xored_line(&candidates);
+  println!("Line {index}, key {key:#04x}: {}", String::from_utf8_lossy(&plaintext));
+
   Ok(())
 }
 // FOUND
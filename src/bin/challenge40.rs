@@ -1,32 +1,19 @@
-use cryptopals::utils::{
-  algebra::{bigint_utils::cbrt, modulo::inv_mod},
-  rsa::RSA,
-};
+use cryptopals::utils::{algebra::modulo::mod_exp, rsa::RSA};
 use num_bigint::BigUint;
 
 fn main() {
   let plaintext = String::from("BOCA");
-  let keys_1 = RSA::generate_keys_with_given_size(64);
-  let keys_2 = RSA::generate_keys_with_given_size(64);
-  let keys_3 = RSA::generate_keys_with_given_size(64);
+  let e = BigUint::from(3u8);
+  let keys_1 = RSA::generate_keys_with_given_params(&e, 64);
+  let keys_2 = RSA::generate_keys_with_given_params(&e, 64);
+  let keys_3 = RSA::generate_keys_with_given_params(&e, 64);
 
-  let result = {
-    let (c1, c2, c3) = (
-      BigUint::from_bytes_be(RSA::encrypt_with_key(&keys_1.pk, &plaintext).as_ref()),
-      BigUint::from_bytes_be(RSA::encrypt_with_key(&keys_2.pk, &plaintext).as_ref()),
-      BigUint::from_bytes_be(RSA::encrypt_with_key(&keys_3.pk, &plaintext).as_ref()),
-    );
-    let (n1, n2, n3) = (keys_1.pk.1, keys_2.pk.1, keys_3.pk.1);
-    let (m1, m2, m3) = (&n2 * &n3, &n1 * &n3, &n1 * &n2);
-    let n = &n1 * &n2 * &n3;
-    let (r1, r2, r3) = (
-      &c1 * &m1 * inv_mod(&m1, &n1).unwrap(),
-      &c2 * &m2 * inv_mod(&m2, &n2).unwrap(),
-      &c3 * &m3 * inv_mod(&m3, &n3).unwrap(),
-    );
-    let r = (r1 + r2 + r3) % n;
-    cbrt(&r)
-  };
+  // The attack needs the message to be encrypted without padding
+  let m = BigUint::from_bytes_be(plaintext.as_bytes());
+  let (n1, n2, n3) = (keys_1.pk.1, keys_2.pk.1, keys_3.pk.1);
+  let (c1, c2, c3) = (mod_exp(&m, &e, &n1), mod_exp(&m, &e, &n2), mod_exp(&m, &e, &n3));
+
+  let result = RSA::hastad_broadcast(&[c1, c2, c3], &[n1, n2, n3], 3).unwrap();
   dbg!(String::from_utf8_lossy(result.to_bytes_be().as_ref()));
   assert_eq!(plaintext.as_bytes().to_vec(), result.to_bytes_be())
 }
@@ -1,5 +1,6 @@
 use cryptopals::utils::{
   algebra::modulo::{inv_mod, mod_exp},
+  conversion::conversion::left_pad_to,
   mac::sha1::{Sha1, Sha1Digest},
   padding::pkcs1_unpad,
   rsa::{RSAKeys, RSA},
@@ -81,9 +82,7 @@ fn main() {
   for px_chunk in dif_plaintext.chunks(chunk_size) {
     let px = BigUint::from_bytes_be(px_chunk);
     let p = (&px * &inv_s) % &n;
-    let p_bytes = p.to_bytes_be();
-    let zeros = chunk_size - p_bytes.len();
-    let extended_p = [vec![0; zeros], p_bytes].concat();
+    let extended_p = left_pad_to(p.to_bytes_be(), chunk_size).unwrap();
     let unpadded_p = pkcs1_unpad(&extended_p);
     plaintext.extend(unpadded_p);
   }
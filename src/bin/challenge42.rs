@@ -1,6 +1,8 @@
 use cryptopals::utils::{
   algebra::bigint_utils::cbrt,
+  conversion::conversion::left_pad_to,
   mac::sha1::{Sha1, Sha1Digest},
+  padding::{pkcs1_pad, pkcs1_unpad},
   rsa::{RSAKeys, RSA},
 };
 use num_bigint::BigUint;
@@ -13,17 +15,27 @@ struct SignerVerifierAPI {
 impl SignerVerifierAPI {
   fn start() -> Self {
     Self {
-      keys: RSA::generate_keys(),
+      keys: RSA::generate_keys_with_given_params(&BigUint::from(3u32), 1024),
     }
   }
 
+  fn n_size(&self) -> usize {
+    self.keys.pk.1.bits().div_ceil(8) as usize
+  }
+
   fn sign(&mut self, digest: &Sha1Digest) -> Vec<u8> {
-    RSA::encrypt_with_key(&self.keys.sk, digest)
+    let m = BigUint::from_bytes_be(&pkcs1_pad(digest, self.n_size()));
+    RSA::raw_private(&m, &self.keys.sk).to_bytes_be()
   }
 
+  // The bad verifier: it only checks that the digest comes right after the padding, not that nothing follows it
   fn verify<S: AsRef<[u8]>>(&mut self, message: &S, signature: &Vec<u8>) -> bool {
     let expected_digest = Sha1::hash(&message);
-    let data = RSA::decrypt_with_key(&self.keys.pk, signature);
+    let m = RSA::raw_public(&BigUint::from_bytes_be(signature), &self.keys.pk);
+    let data = pkcs1_unpad(&left_pad_to(m.to_bytes_be(), self.n_size()).unwrap());
+    if data.len() < 20 {
+      return false;
+    }
     let obtained_digest: Sha1Digest = data[0..20].try_into().unwrap();
     expected_digest == obtained_digest
   }
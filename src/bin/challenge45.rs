@@ -1,4 +1,4 @@
-use cryptopals::utils::{algebra::modulo::{inv_mod, mod_exp}, dsa::{SignatureAlgorithm, DSA}};
+use cryptopals::utils::dsa::{SignatureAlgorithm, DSA};
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 fn main() {
@@ -11,11 +11,11 @@ fn main() {
   /*
     g = 0 -> r = 0. Suppose we do not verify 0 < r.
     Then v = 0 and the signature will always validate, whatever the value of s.
-    NOTE: We only modified the DSA code for this challenge. If you want the attack to work, remove the r > 0 assertion
+    NOTE: DSA::verify now checks 0 < r, so this forgery is rejected. Remove that check to see the attack work
   */
   dsa.g = BigUint::zero();
   let forged_signature_1 = (BigUint::zero(), BigUint::from(123456789u32));
-  assert!(dsa.verify(&y, message, &forged_signature_1));
+  assert!(!dsa.verify(&y, message, &forged_signature_1));
 
   /*
     g = p + 1 -> g = 1 (mod p).
@@ -26,11 +26,12 @@ fn main() {
       = y^z (mod p) (mod q)
       = r
   */
-  let (p, q, _) = dsa.get_params();
+  let (p, _, _) = dsa.get_params();
   dsa.g = &p + BigUint::one();
-  let z = BigUint::from(234567u32);
-  let r = mod_exp(&y, &z, &p) % &q;
-  let s = (&r * inv_mod(&z, &q).unwrap()) % q;
-  let forged_signature_2 = (r, s);
+  let forged_signature_2 = dsa.forge_magic_signature(&y, &BigUint::from(234567u32));
   assert!(dsa.verify(&y, message, &forged_signature_2));
+
+  // The forgery is rejected when the verifier refuses degenerate generators
+  dsa.strict_params = true;
+  assert!(!dsa.verify(&y, message, &forged_signature_2));
 }
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use cryptopals::utils::{
   algebra::modulo::{inv_mod, mod_exp},
+  conversion::conversion::left_pad_to,
   rsa::{RSAKeys, RSA},
 };
 use num::Integer;
@@ -53,8 +54,7 @@ impl RSAPaddingOracle {
     let (d, n) = &self.keys.sk;
     let n_size = ((n.bits() + 7) / 8) as usize;
     let plaintext = mod_exp(&c, &d, &n).to_bytes_be();
-    let zeros = n_size - plaintext.len();
-    let with_trailing_zeros = [vec![0x00; zeros], plaintext].concat();
+    let with_trailing_zeros = left_pad_to(plaintext, n_size).unwrap();
     with_trailing_zeros.len() > 11
       && with_trailing_zeros[0] == 0x00
       && with_trailing_zeros[1] == 0x02
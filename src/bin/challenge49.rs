@@ -25,10 +25,13 @@
   In fact, we can do it for every byte values of the ids (doing their xor) and we put that byte in the correct IV positions.
 */
 
-use cryptopals::utils::aes::{aes::AES, aes_key::AESKey, utils::AESMode};
+use cryptopals::utils::{
+  aes::{aes::AES, aes_key::AESKey, utils::AESMode},
+  mac::cbc_mac::{cbc_mac, forge_cbc_mac_extension, forge_cbc_mac_iv},
+};
 use regex::Regex;
 
-type MessageT = String;
+type MessageT = Vec<u8>;
 type MacT = Vec<u8>;
 type IvT = [u8; 16];
 type TransactionT = (u8, u64);
@@ -51,7 +54,7 @@ impl Client {
     to: u8,
     amount: u64,
   ) -> (MessageT, MacT) {
-    let msg = format!("from={}&to={}&amount={}", self.id, to, amount);
+    let msg = format!("from={}&to={}&amount={}", self.id, to, amount).into_bytes();
     let mac = AES::encode(&msg, &server.get_key_to_sign(), AESMode::CBC(iv)).unwrap();
     (msg, mac)
   }
@@ -67,8 +70,8 @@ impl Client {
       .map(|(to, amount)| format!("{}:{};", to, amount))
       .collect::<Vec<String>>()
       .join("");
-    let msg = format!("from:{}&tx_list={}", self.id, txs_string);
-    let mac = AES::encode(&msg, &server.get_key_to_sign(), AESMode::CBC(iv)).unwrap();
+    let msg = format!("from={}&tx_list={}", self.id, txs_string).into_bytes();
+    let mac = cbc_mac(&msg, &server.get_key_to_sign(), iv).to_vec();
     (msg, mac)
   }
 }
@@ -103,8 +106,9 @@ impl Challenge49Server for ServerPart1 {
   Now for the second version, it should be clear that the IV should be fixed. So IV = 0
   The server S recieves data of the form: message || MAC
   message: from=#{from_id}&tx_list=#{transactions}
-  transactions: to:amount(;to:amount)*  (do the regex)
-  ^from=(\d+)&tx_list=((\d+:\d+)(;to:\d+:\d+)*)$
+  transactions: to:amount(;to:amount)*
+  The server only validates the prefix ^from=(\d+)&tx_list=\d+:\d+ and ignores whatever garbage comes after it,
+  as the challenge assumes. That is what lets the glued blocks below through
 
   An attacker could extend the message. Because the MAC = Cn would be a valid IV to add transactions
   Cn+1 = E(K, Pn+1 xor MAC) where Pn+1 = 1:1000000 (and the padding)
@@ -128,14 +132,14 @@ impl Challenge49Server for ServerPart2 {
     self.key.clone()
   }
 
+  // Like a lenient parser, only the start of the message is checked, so the bytes glued by the attacker pass
   fn verify_signature(&self, msg: &MessageT, _iv: IvT, mac: &MacT) -> bool {
-    let re = Regex::new(r"^from=(\d+)&tx_list=((\d+:\d+)(;to:\d+:\d+)*)$").unwrap();
+    let re = Regex::new(r"^from=(\d+)&tx_list=\d+:\d+").unwrap();
     let input = String::from_utf8_lossy(msg.as_ref());
     if re.captures(&input).is_none() {
       return false;
     }
-    let ciphertext = AES::encode(&msg, &self.key, AESMode::CBC(self.iv)).unwrap();
-    mac == &ciphertext[ciphertext.len() - 32..]
+    mac == &cbc_mac(msg, &self.key, self.iv)
   }
 }
 
@@ -151,21 +155,23 @@ fn main() {
   let (msg1, mac1) = attacker.sign_part1(&server1, iv1, victim_id, high_amount);
   assert!(server1.verify_signature(&msg1, iv1, &mac1));
 
-  let mut msg1_bytes = msg1.into_bytes();
-  let (attacker_byte, victim_byte) = (msg1_bytes[6], msg1_bytes[11]);
-  let b = attacker_byte ^ victim_byte;
-  let mut forged_iv1 = iv1.clone();
-  forged_iv1[6] = b;
-  forged_iv1[11] = b;
-  msg1_bytes.swap(6, 11);
-  let forged_msg1 = String::from_utf8(msg1_bytes.to_vec()).unwrap();
+  let (forged_msg1, forged_iv1) = forge_cbc_mac_iv(&msg1, &iv1, &[(5, 10)]);
   assert!(server1.verify_signature(&forged_msg1, forged_iv1, &mac1));
 
   // PART 2
   let server2 = ServerPart2::new();
   let iv2 = [0; 16];
   let (msg2, mac2) = victim.sign_part2(&server2, iv2, &vec![(third_id, low_amount)]);
-  server2.verify_signature(&msg2, iv2, &mac2);
-  // I also should add the PKCS padding scheme here. Q = 1:1000000
-  // The idea is to encrypt CBC-MAC(K, Q ^ mac2) = mac2' and use that as the mac for msg2 || Q
+  assert!(server2.verify_signature(&msg2, iv2, &mac2));
+  // The attacker signs a message of their own whose tail is the transaction they want to add
+  // and glues it after the victim's message, fixing the first block with the victim's MAC
+  let (attacker_msg, attacker_mac) = attacker.sign_part2(&server2, iv2, &vec![(third_id, 1), (attacker_id, high_amount)]);
+  let key2 = server2.get_key_to_sign();
+  let victim_mac: [u8; 16] = mac2.as_slice().try_into().unwrap();
+  let forged_msg2 = forge_cbc_mac_extension(&msg2, &victim_mac, &attacker_msg);
+  assert!(forged_msg2.ends_with(format!(";{}:{};", attacker_id, high_amount).as_bytes()));
+  assert_eq!(cbc_mac(&forged_msg2, &key2, iv2), cbc_mac(&attacker_msg, &key2, iv2));
+  // The server accepts the victim's message with the attacker's transaction appended
+  assert!(server2.verify_signature(&forged_msg2, iv2, &attacker_mac));
+  assert!(forged_msg2.starts_with(format!("from={}&tx_list=", victim_id).as_bytes()));
 }
@@ -1,6 +1,6 @@
 use cryptopals::utils::{
-  aes::{aes::AES, utils::AESMode},
   conversion::{conversion::xor_bytes_vectors, hex_string::HexString},
+  mac::cbc_mac::cbc_mac,
 };
 
 /*
@@ -9,17 +9,12 @@ use cryptopals::utils::{
   Do not use MAC as hash functions.
 */
 
-fn cbc_mac<S: AsRef<[u8]>, T: AsRef<[u8]>>(msg: &S, key: &T, iv: [u8; 16]) -> [u8; 16] {
-  let cip = AES::encode(msg, &key, AESMode::CBC(iv)).unwrap();
-  cip[cip.len() - 16..].try_into().unwrap()
-}
-
 fn main() {
   // msg1 = P1 || P2 has padding when using the cbc_mac
   let msg1 = b"alert('MZA who was that?');\n";
   let key = b"YELLOW SUBMARINE";
   let mac1 = cbc_mac(msg1, key, [0; 16]);
-  println!("{}", HexString::try_from(mac1.to_vec()).unwrap());
+  println!("{}", HexString::from_array(&mac1));
 
   // msg2 = Q1 || Q2 has exactly two blocks without padding
   // Whatever we put right after msg2 will be ignored because it is behind a JS comment
@@ -37,7 +32,7 @@ fn main() {
   println!(
     "{} has a CBC-MAC of {}",
     String::from_utf8_lossy(msg3.as_ref()),
-    HexString::try_from(mac1.to_vec()).unwrap()
+    HexString::from_array(&mac1)
   );
   // It works in the DOM!
 }
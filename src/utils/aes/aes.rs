@@ -1,11 +1,36 @@
-// This is an implementation of AES-128 in ECB mode
+// This is an implementation of AES-128, AES-192 and AES-256 in ECB, CBC, CTR, CFB, OFB and GCM modes
 use super::{
   aes_block::AESBlock,
   aes_error::AESError,
   aes_key::AESKey,
   constants::*,
-  utils::{pkcs_padding, word_modifier, AESMode},
+  utils::{constant_time_eq, pkcs_padding, strip_pkcs_padding, AESMode, CtrConfig},
 };
+use crate::utils::{
+  algebra::gf128::{ghash, Gf128},
+  conversion::hex_string::HexString,
+};
+
+pub const GCM_TAG_SIZE: usize = 16;
+
+// (key, plaintext, ciphertext) of FIPS-197 Appendix C, one for each key size
+const FIPS_197_KAT_VECTORS: [(&str, &str, &str); 3] = [
+  (
+    "000102030405060708090a0b0c0d0e0f",
+    "00112233445566778899aabbccddeeff",
+    "69c4e0d86a7b0430d8cdb78070b4c55a",
+  ),
+  (
+    "000102030405060708090a0b0c0d0e0f1011121314151617",
+    "00112233445566778899aabbccddeeff",
+    "dda97ca4864cdfe06eaf70a0ec0d7191",
+  ),
+  (
+    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+    "00112233445566778899aabbccddeeff",
+    "8ea2b7ca516745bfeafc49904b496089",
+  ),
+];
 
 pub struct AES {
   pub key: AESKey,
@@ -28,6 +53,14 @@ impl AES {
     Ok(())
   }
 
+  fn validate_block_aligned<S: AsRef<[u8]>>(text: &S) -> Result<(), AESError> {
+    let length = text.as_ref().len();
+    if length == 0 || length % AES_BLOCK_SIZE != 0 {
+      return Err(AESError::InvalidBlockSize(length));
+    }
+    Ok(())
+  }
+
   pub fn key_size(&self) -> usize {
     self.key.size()
   }
@@ -47,44 +80,43 @@ impl AES {
     Ok(blocks)
   }
 
-  pub fn compute_all_round_keys(&self) -> [AESKey; AES128_ROUNDS] {
-    match self.key {
-      AESKey::AES128Key(_) => self.aes_128_compute_all_round_keys(),
-      AESKey::AES192Key(_) => unimplemented!(),
-      AESKey::AES256Key(_) => unimplemented!(),
-    }
+  // The rounds() + 1 round keys of any key size. The first one is used for the initial add_round_key
+  pub fn get_round_keys(&self) -> Vec<AESKey> {
+    self
+      .key
+      .expand_key()
+      .iter()
+      .map(|block| AESKey::AES128Key(block.as_flatten_array()))
+      .collect()
   }
 
-  fn reverse_keys_for_decryption(&self, keys: &mut Vec<AESKey>) -> AESKey {
-    keys.reverse();
-    keys.push(self.key);
-    keys.pop().unwrap()
+  // Round keys 1..=rounds(), i.e. every round key after the initial add_round_key
+  pub fn compute_all_round_keys(&self) -> Vec<AESKey> {
+    self.get_round_keys().split_off(1)
   }
 
-  fn aes_128_compute_all_round_keys(&self) -> [AESKey; AES128_ROUNDS] {
-    const WK: usize = 4; // words-per-key
-    let mut words: [(u8, u8, u8, u8); WK * (AES128_ROUNDS + 1)] =
-      [(0, 0, 0, 0); WK * (AES128_ROUNDS + 1)];
-    words[0..4].copy_from_slice(self.key.divide_in_words().as_slice());
-
-    for i in WK..WK * (AES128_ROUNDS + 1) {
-      let mut temp = words[i - 1]; // previous word
-      if i % WK == 0 {
-        temp = word_modifier(temp, (i / WK) as u8);
+  // Encrypts one block recording the state after every step, labeled as "round {r} {step}".
+  // Round 0 is the initial add_round_key, and the last round has no mix_columns
+  pub fn encrypt_block_trace(&self, block: &AESBlock) -> Vec<(String, AESBlock)> {
+    let round_keys = self.key.expand_key();
+    let rounds = round_keys.len() - 1;
+    let mut state = *block;
+    let mut trace = vec![(String::from("input"), state)];
+    state.xor_with_block(&round_keys[0]);
+    trace.push((String::from("round 0 add_round_key"), state));
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+      state.sub_bytes();
+      trace.push((format!("round {round} sub_bytes"), state));
+      state.shift_rows();
+      trace.push((format!("round {round} shift_rows"), state));
+      if round < rounds {
+        state.mix_columns(false);
+        trace.push((format!("round {round} mix_columns"), state));
       }
-      words[i].0 = words[i - WK].0 ^ temp.0;
-      words[i].1 = words[i - WK].1 ^ temp.1;
-      words[i].2 = words[i - WK].2 ^ temp.2;
-      words[i].3 = words[i - WK].3 ^ temp.3;
+      state.xor_with_block(round_key);
+      trace.push((format!("round {round} add_round_key"), state));
     }
-
-    let mut round_keys: [AESKey; AES128_ROUNDS] = [AESKey::default_value(); AES128_ROUNDS];
-    for i in 0..AES128_ROUNDS {
-      let word_group: [(u8, u8, u8, u8); 4] = words[4 * (i + 1)..4 * (i + 2)].try_into().unwrap();
-      round_keys[i] = AESKey::from_words(word_group);
-    }
-
-    round_keys.try_into().unwrap()
+    trace
   }
 
   fn return_blocks_as_bytes(blocks: &Vec<AESBlock>) -> Vec<u8> {
@@ -102,157 +134,310 @@ impl AES {
   ) -> Result<Vec<u8>, AESError> {
     let aes = Self::create_from(key_bytes, mode)?;
     match aes.mode {
-      AESMode::ECB => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_ecb_encode(plaintext),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::CBC(iv) => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_cbc_encode(plaintext, &iv),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::CTR(nonce) => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_ctr(plaintext, nonce),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::GCM => unimplemented!(),
+      AESMode::ECB => aes.ecb_encode(&pkcs_padding(plaintext, AES_BLOCK_SIZE as u8)),
+      AESMode::CBC(iv) => aes.cbc_encode(&pkcs_padding(plaintext, AES_BLOCK_SIZE as u8), &iv),
+      AESMode::CTR(nonce) => aes.ctr(plaintext, nonce),
+      AESMode::CTRWithConfig { ref nonce, config } => aes.ctr_with_config(plaintext, nonce, &config),
+      AESMode::CFB(iv) => Ok(aes.cfb_encode(plaintext, &iv)),
+      AESMode::OFB(iv) => Ok(aes.ofb(plaintext, &iv)),
+      AESMode::GCM { ref nonce, ref aad } => Ok(aes.gcm_encode(plaintext, nonce, aad)),
     }
   }
 
+  // ECB over input that is already block aligned, as for hashers that encrypt exactly one block.
+  // Misaligned input is an error instead of being padded
+  pub fn encode_ecb_nopad<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+    plaintext: &S,
+    key_bytes: &T,
+  ) -> Result<Vec<u8>, AESError> {
+    Self::validate_block_aligned(plaintext)?;
+    Self::create_from(key_bytes, AESMode::ECB)?.ecb_encode(plaintext)
+  }
+
+  // ECB and CBC check and strip the PKCS#7 padding, an invalid one is a PaddingError
   pub fn decode<S: AsRef<[u8]>, T: AsRef<[u8]>>(
     ciphertext: &S,
     key_bytes: &T,
     mode: AESMode,
+  ) -> Result<Vec<u8>, AESError> {
+    let is_padded = matches!(mode, AESMode::ECB | AESMode::CBC(_));
+    let plaintext = Self::decode_no_unpad(ciphertext, key_bytes, mode)?;
+    if is_padded {
+      strip_pkcs_padding(&plaintext, AES_BLOCK_SIZE as u8)
+    } else {
+      Ok(plaintext)
+    }
+  }
+
+  // Returns the plaintext with its padding as is, for the padding oracle attacks that need to look at it
+  pub fn decode_no_unpad<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+    ciphertext: &S,
+    key_bytes: &T,
+    mode: AESMode,
   ) -> Result<Vec<u8>, AESError> {
     let aes = Self::create_from(key_bytes, mode)?;
 
     match aes.mode {
-      AESMode::ECB => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_ecb_decode(ciphertext),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::CBC(iv) => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_cbc_decode(ciphertext, &iv),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::CTR(nonce) => match aes.key {
-        AESKey::AES128Key(_) => aes.aes_128_ctr(ciphertext, nonce),
-        AESKey::AES192Key(_) => unimplemented!(),
-        AESKey::AES256Key(_) => unimplemented!(),
-      },
-      AESMode::GCM => unimplemented!(),
+      AESMode::ECB => aes.ecb_decode(ciphertext),
+      AESMode::CBC(iv) => aes.cbc_decode(ciphertext, &iv),
+      AESMode::CTR(nonce) => aes.ctr(ciphertext, nonce),
+      AESMode::CTRWithConfig { ref nonce, config } => aes.ctr_with_config(ciphertext, nonce, &config),
+      AESMode::CFB(iv) => Ok(aes.cfb_decode(ciphertext, &iv)),
+      AESMode::OFB(iv) => Ok(aes.ofb(ciphertext, &iv)),
+      AESMode::GCM { ref nonce, ref aad } => aes.gcm_decode(ciphertext, nonce, aad),
     }
   }
 
-  fn aes_128_ecb_encode<S: AsRef<[u8]>>(&self, plaintext: &S) -> Result<Vec<u8>, AESError> {
-    let padded_text = pkcs_padding(plaintext, AES_BLOCK_SIZE as u8);
-    let mut blocks = Self::divide_in_blocks(&padded_text)?;
-    let keys = self.aes_128_compute_all_round_keys();
+  // The number of rounds is given by the amount of round keys, so this works for every key size
+  pub fn encrypt_block(block: &mut AESBlock, keys: &[AESKey]) {
+    #[cfg(feature = "instrument")]
+    super::instrument::count_block_operation();
+    let rounds = keys.len() - 1;
+    block.add_round_key(&keys[0]);
+    for (round, key) in keys.iter().enumerate().skip(1) {
+      block.apply_round(key, round == rounds);
+    }
+  }
 
-    for block in blocks.iter_mut() {
-      block.add_round_key(&self.key);
-      for round in 0..AES128_ROUNDS {
-        block.apply_round(&keys[round], round == AES128_ROUNDS - 1);
-      }
+  fn decrypt_block(block: &mut AESBlock, keys: &[AESKey]) {
+    #[cfg(feature = "instrument")]
+    super::instrument::count_block_operation();
+    let rounds = keys.len() - 1;
+    for round in (1..=rounds).rev() {
+      block.apply_inverse_round(&keys[round], round == rounds);
     }
+    block.add_round_key(&keys[0]);
+  }
 
-    let ciphertext: Vec<u8> = blocks
-      .iter()
-      .map(|arr| arr.as_flatten_array())
-      .flatten()
-      .collect();
-    Ok(ciphertext)
+  // ECB and CBC encoding take the already padded plaintext
+  fn ecb_encode<S: AsRef<[u8]>>(&self, plaintext: &S) -> Result<Vec<u8>, AESError> {
+    let mut blocks = Self::divide_in_blocks(plaintext)?;
+    let keys = self.get_round_keys();
+    for block in blocks.iter_mut() {
+      Self::encrypt_block(block, &keys);
+    }
+    Ok(Self::return_blocks_as_bytes(&blocks))
   }
 
-  fn aes_128_ecb_decode<S: AsRef<[u8]>>(&self, ciphertext: &S) -> Result<Vec<u8>, AESError> {
-    let mut keys = self.aes_128_compute_all_round_keys().to_vec();
-    let last_key = self.reverse_keys_for_decryption(&mut keys);
-    let padded_text = pkcs_padding(ciphertext, AES_BLOCK_SIZE as u8);
-    let mut blocks = Self::divide_in_blocks(&padded_text)?;
+  fn ecb_decode<S: AsRef<[u8]>>(&self, ciphertext: &S) -> Result<Vec<u8>, AESError> {
+    Self::validate_block_aligned(ciphertext)?;
+    let mut blocks = Self::divide_in_blocks(ciphertext)?;
+    let keys = self.get_round_keys();
     for block in blocks.iter_mut() {
-      for round in 0..AES128_ROUNDS {
-        block.apply_inverse_round(&keys[round], round == 0);
-      }
-      block.add_round_key(&last_key);
+      Self::decrypt_block(block, &keys);
     }
     Ok(Self::return_blocks_as_bytes(&blocks))
   }
 
-  fn aes_128_cbc_encode<S: AsRef<[u8]>>(
+  fn cbc_encode<S: AsRef<[u8]>>(
     &self,
     plaintext: &S,
     iv: &[u8; 16],
   ) -> Result<Vec<u8>, AESError> {
-    let keys = self.aes_128_compute_all_round_keys();
-    let padded_text = pkcs_padding(plaintext, AES_BLOCK_SIZE as u8);
-    let mut blocks = Self::divide_in_blocks(&padded_text)?;
-
-    blocks[0].xor_with_block(&AESBlock::from_flat_array(iv));
-    blocks[0].add_round_key(&self.key);
-    for round in 0..AES128_ROUNDS {
-      blocks[0].apply_round(&keys[round], round == AES128_ROUNDS - 1);
-    }
-
-    for i in 1..blocks.len() {
-      let previous_block = blocks[i - 1];
-      blocks[i].xor_with_block(&previous_block);
-      blocks[i].add_round_key(&self.key);
-      for round in 0..AES128_ROUNDS {
-        blocks[i].apply_round(&keys[round], round == AES128_ROUNDS - 1);
-      }
+    let keys = self.get_round_keys();
+    let mut blocks = Self::divide_in_blocks(plaintext)?;
+    let mut previous_block = AESBlock::from_flat_array(iv);
+    for block in blocks.iter_mut() {
+      block.xor_with_block(&previous_block);
+      Self::encrypt_block(block, &keys);
+      previous_block = *block;
     }
     Ok(Self::return_blocks_as_bytes(&blocks))
   }
 
-  fn aes_128_cbc_decode<S: AsRef<[u8]>>(
+  fn cbc_decode<S: AsRef<[u8]>>(
     &self,
     ciphertext: &S,
     iv: &[u8; 16],
   ) -> Result<Vec<u8>, AESError> {
-    let mut keys = self.aes_128_compute_all_round_keys().to_vec();
-    let last_key = self.reverse_keys_for_decryption(&mut keys);
-
-    let padded_text = pkcs_padding(ciphertext, AES_BLOCK_SIZE as u8);
-    let mut blocks = Self::divide_in_blocks(&padded_text)?;
-    let ciphered_blocks = blocks.clone();
-    for round in 0..AES128_ROUNDS {
-      blocks[0].apply_inverse_round(&keys[round], round == 0);
-    }
-    blocks[0].add_round_key(&last_key);
-    blocks[0].xor_with_block(&AESBlock::from_flat_array(iv));
-
-    for i in 1..blocks.len() {
-      for round in 0..AES128_ROUNDS {
-        blocks[i].apply_inverse_round(&keys[round], round == 0);
-      }
-      blocks[i].add_round_key(&last_key);
-      blocks[i].xor_with_block(&ciphered_blocks[i - 1]);
+    Self::validate_block_aligned(ciphertext)?;
+    let keys = self.get_round_keys();
+    let mut blocks = Self::divide_in_blocks(ciphertext)?;
+    let mut previous_block = AESBlock::from_flat_array(iv);
+    for block in blocks.iter_mut() {
+      let ciphered_block = *block;
+      Self::decrypt_block(block, &keys);
+      block.xor_with_block(&previous_block);
+      previous_block = ciphered_block;
     }
     Ok(Self::return_blocks_as_bytes(&blocks))
   }
 
-  fn aes_128_ctr<S: AsRef<[u8]>>(
+  fn ctr<S: AsRef<[u8]>>(
     &self,
     text: &S,
     nonce: u64,
   ) -> Result<Vec<u8>, AESError> {
+    self.ctr_with_config(text, &nonce.to_le_bytes(), &CtrConfig::default())
+  }
+
+  fn ctr_with_config<S: AsRef<[u8]>>(
+    &self,
+    text: &S,
+    nonce: &[u8],
+    config: &CtrConfig,
+  ) -> Result<Vec<u8>, AESError> {
+    config.validate(nonce)?;
+    let keys = self.get_round_keys();
     let mut result: Vec<u8> = Vec::new();
-    let mut ctr: u64 = 0;
 
-    for chunk in text.as_ref().chunks(16) {
-      let b = [nonce.to_le_bytes(), ctr.to_le_bytes()].concat();
-      let s = self.aes_128_ecb_encode(&b)?;
+    for (ctr, chunk) in (0u64..).zip(text.as_ref().chunks(16)) {
+      let b = config.counter_block(nonce, ctr);
+      let mut keystream_block = AESBlock::from_flat_array(&b);
+      Self::encrypt_block(&mut keystream_block, &keys);
+      let s = keystream_block.as_flatten_array();
       let mut block = chunk.to_vec();
       for (i, byte) in block.iter_mut().enumerate() {
         *byte ^= s[i];
       }
       result.extend_from_slice(&block);
-      ctr += 1;
     }
     Ok(result)
   }
+
+  // CFB and OFB are stream modes, so there is no padding and the last block may be partial.
+  // In CFB the keystream is E(k, previous ciphertext block), starting from E(k, IV)
+  fn cfb_encode<S: AsRef<[u8]>>(&self, plaintext: &S, iv: &[u8; 16]) -> Vec<u8> {
+    let keys = self.get_round_keys();
+    let mut result: Vec<u8> = Vec::with_capacity(plaintext.as_ref().len());
+    let mut previous_block = AESBlock::from_flat_array(iv);
+    for chunk in plaintext.as_ref().chunks(AES_BLOCK_SIZE) {
+      Self::encrypt_block(&mut previous_block, &keys);
+      let ciphered: Vec<u8> = chunk.iter().zip(previous_block.as_flatten_array()).map(|(b, k)| b ^ k).collect();
+      if ciphered.len() == AES_BLOCK_SIZE {
+        previous_block = AESBlock::from_flat_array(&ciphered.clone().try_into().unwrap());
+      }
+      result.extend(ciphered);
+    }
+    result
+  }
+
+  fn cfb_decode<S: AsRef<[u8]>>(&self, ciphertext: &S, iv: &[u8; 16]) -> Vec<u8> {
+    let keys = self.get_round_keys();
+    let mut result: Vec<u8> = Vec::with_capacity(ciphertext.as_ref().len());
+    let mut previous_block = AESBlock::from_flat_array(iv);
+    for chunk in ciphertext.as_ref().chunks(AES_BLOCK_SIZE) {
+      Self::encrypt_block(&mut previous_block, &keys);
+      result.extend(chunk.iter().zip(previous_block.as_flatten_array()).map(|(b, k)| b ^ k));
+      if chunk.len() == AES_BLOCK_SIZE {
+        previous_block = AESBlock::from_flat_array(&chunk.try_into().unwrap());
+      }
+    }
+    result
+  }
+
+  // In OFB the keystream is E(k, IV), E(k, E(k, IV)), ... so encoding and decoding are the same
+  fn ofb<S: AsRef<[u8]>>(&self, text: &S, iv: &[u8; 16]) -> Vec<u8> {
+    let keys = self.get_round_keys();
+    let mut result: Vec<u8> = Vec::with_capacity(text.as_ref().len());
+    let mut keystream_block = AESBlock::from_flat_array(iv);
+    for chunk in text.as_ref().chunks(AES_BLOCK_SIZE) {
+      Self::encrypt_block(&mut keystream_block, &keys);
+      result.extend(chunk.iter().zip(keystream_block.as_flatten_array()).map(|(b, k)| b ^ k));
+    }
+    result
+  }
+
+  // The counter block is nonce || ctr with ctr as a 32-bit big-endian integer. J0 has ctr = 1 and
+  // masks the tag, so the message is encrypted from ctr = 2 on
+  fn gcm_counter_block(nonce: &[u8; 12], ctr: u32) -> AESBlock {
+    let b: [u8; 16] = [nonce.as_slice(), &ctr.to_be_bytes()].concat().try_into().unwrap();
+    AESBlock::from_flat_array(&b)
+  }
+
+  fn gcm_ctr(text: &[u8], nonce: &[u8; 12], keys: &[AESKey]) -> Vec<u8> {
+    let mut result: Vec<u8> = Vec::with_capacity(text.len());
+    for (ctr, chunk) in (2u32..).zip(text.chunks(AES_BLOCK_SIZE)) {
+      let mut keystream_block = Self::gcm_counter_block(nonce, ctr);
+      Self::encrypt_block(&mut keystream_block, keys);
+      let s = keystream_block.as_flatten_array();
+      result.extend(chunk.iter().zip(s).map(|(byte, k)| byte ^ k));
+    }
+    result
+  }
+
+  fn gcm_tag(ciphertext: &[u8], nonce: &[u8; 12], aad: &[u8], keys: &[AESKey]) -> [u8; GCM_TAG_SIZE] {
+    let mut h = AESBlock::from_flat_array(&[0; 16]);
+    Self::encrypt_block(&mut h, keys);
+    let mut j0 = Self::gcm_counter_block(nonce, 1);
+    Self::encrypt_block(&mut j0, keys);
+    let s = ghash(Gf128::from_be_bytes(h.as_flatten_array()), aad, ciphertext);
+    (s + Gf128::from_be_bytes(j0.as_flatten_array())).to_be_bytes()
+  }
+
+  // Returns the ciphertext followed by the 16 bytes tag
+  fn gcm_encode<S: AsRef<[u8]>>(&self, plaintext: &S, nonce: &[u8; 12], aad: &[u8]) -> Vec<u8> {
+    let keys = self.get_round_keys();
+    let mut ciphertext = Self::gcm_ctr(plaintext.as_ref(), nonce, &keys);
+    let tag = Self::gcm_tag(&ciphertext, nonce, aad, &keys);
+    ciphertext.extend_from_slice(&tag);
+    ciphertext
+  }
+
+  // The tag is checked before decrypting, nothing is returned for a forged ciphertext
+  fn gcm_decode<S: AsRef<[u8]>>(
+    &self,
+    ciphertext: &S,
+    nonce: &[u8; 12],
+    aad: &[u8],
+  ) -> Result<Vec<u8>, AESError> {
+    let ciphertext = ciphertext.as_ref();
+    if ciphertext.len() < GCM_TAG_SIZE {
+      return Err(AESError::InvalidBlockSize(ciphertext.len()));
+    }
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - GCM_TAG_SIZE);
+    let keys = self.get_round_keys();
+    if !constant_time_eq(&Self::gcm_tag(ciphertext, nonce, aad, &keys), tag) {
+      return Err(AESError::AuthenticationFailed);
+    }
+    Ok(Self::gcm_ctr(ciphertext, nonce, &keys))
+  }
+
+  // Known-answer test: encrypts and decrypts the FIPS-197 vectors of every key size.
+  // Meant to be run at startup by users of the crate to check the build
+  pub fn run_kat() -> Result<(), String> {
+    Self::run_kat_vectors(&FIPS_197_KAT_VECTORS)
+  }
+
+  fn run_kat_vectors(vectors: &[(&str, &str, &str)]) -> Result<(), String> {
+    let to_bytes = |hex: &str| {
+      HexString::try_from(hex)
+        .and_then(|hex| hex.as_vector_of_bytes())
+        .map_err(|e| e.to_string())
+    };
+    for &(key, plaintext, expected) in vectors {
+      let (key_bytes, plaintext_bytes) = (to_bytes(key)?, to_bytes(plaintext)?);
+      let ciphertext = Self::encode_ecb_nopad(&plaintext_bytes, &key_bytes).map_err(|e| e.to_string())?;
+      let obtained = HexString::try_from(ciphertext.clone()).map_err(|e| e.to_string())?;
+      if obtained != HexString::try_from(expected).map_err(|e| e.to_string())? {
+        let bits = key_bytes.len() * 8;
+        return Err(format!("AES-{bits} with key {key} encrypted {plaintext} to {obtained}, expected {expected}"));
+      }
+      let decrypted = Self::decode_no_unpad(&ciphertext, &key_bytes, AESMode::ECB).map_err(|e| e.to_string())?;
+      if decrypted != plaintext_bytes {
+        return Err(format!("AES-{} with key {key} did not decrypt back to {plaintext}", key_bytes.len() * 8));
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // The AES-192 vector with the last byte of the ciphertext changed
+  const CORRUPTED_KAT_VECTORS: [(&str, &str, &str); 1] = [(
+    "000102030405060708090a0b0c0d0e0f1011121314151617",
+    "00112233445566778899aabbccddeeff",
+    "dda97ca4864cdfe06eaf70a0ec0d7190",
+  )];
+
+  #[test]
+  fn test_run_kat() {
+    assert_eq!(AES::run_kat(), Ok(()));
+    let error = AES::run_kat_vectors(&CORRUPTED_KAT_VECTORS).unwrap_err();
+    assert!(error.starts_with("AES-192"), "{error}");
+    assert!(error.ends_with("expected dda97ca4864cdfe06eaf70a0ec0d7190"), "{error}");
+  }
 }
@@ -2,7 +2,7 @@ use crate::utils::conversion::hex_string::HexString;
 use core::fmt;
 
 use super::{aes_error::AESError, aes_key::AESKey, constants::*};
-use crate::utils::algebra::galois::galois_multiplication;
+use crate::utils::algebra::galois::{GMUL11, GMUL13, GMUL14, GMUL2, GMUL3, GMUL9};
 
 #[derive(Debug, Clone, Copy, Hash)]
 pub struct AESBlock {
@@ -10,23 +10,6 @@ pub struct AESBlock {
 }
 
 impl AESBlock {
-  const MIX_COLUMN_CT: Self = Self {
-    mat: [
-      [0x02, 0x03, 0x01, 0x01],
-      [0x01, 0x02, 0x03, 0x01],
-      [0x01, 0x01, 0x02, 0x03],
-      [0x03, 0x01, 0x01, 0x02],
-    ],
-  };
-  const INV_MIX_COLUMN_CT: Self = Self {
-    mat: [
-      [0x0e, 0x0b, 0x0d, 0x09],
-      [0x09, 0x0e, 0x0b, 0x0d],
-      [0x0d, 0x09, 0x0e, 0x0b],
-      [0x0b, 0x0d, 0x09, 0x0e],
-    ],
-  };
-
   pub fn from_flat_array(arr: &[u8; 16]) -> Self {
     Self {
       mat: [
@@ -72,8 +55,17 @@ impl AESBlock {
     arr.to_owned()
   }
 
+  // Big-endian: the first byte of the block is the most significant one
+  pub fn from_u128(x: u128) -> Self {
+    Self::from_flat_array(&x.to_be_bytes())
+  }
+
+  pub fn to_u128(&self) -> u128 {
+    u128::from_be_bytes(self.as_flatten_array())
+  }
+
   pub fn as_hex_string(&self) -> HexString {
-    HexString::try_from(self.as_flatten_array().to_vec()).unwrap()
+    HexString::from_array(&self.as_flatten_array())
   }
 
   pub fn xor_with_block(&mut self, other_block: &Self) -> &mut Self {
@@ -121,23 +113,17 @@ impl AESBlock {
     self
   }
 
-  fn matrix_gmult(first: &Self, second: &Self) -> [[u8; 4]; 4] {
-    let mut result = [[0; 4]; 4];
-    for i in 0..4 {
-      for j in 0..4 {
-        for k in 0..4 {
-          result[i][j] ^= galois_multiplication(first.mat[i][k], second.mat[k][j]);
-        }
-      }
-    }
-    result
-  }
-
   pub fn mix_columns(&mut self, ignore: bool) -> &mut Self {
     if ignore {
       return self;
     }
-    self.mat = Self::matrix_gmult(&Self::MIX_COLUMN_CT, self);
+    for j in 0..4 {
+      let [a, b, c, d] = [0, 1, 2, 3].map(|i| self.mat[i][j] as usize);
+      self.mat[0][j] = GMUL2[a] ^ GMUL3[b] ^ c as u8 ^ d as u8;
+      self.mat[1][j] = a as u8 ^ GMUL2[b] ^ GMUL3[c] ^ d as u8;
+      self.mat[2][j] = a as u8 ^ b as u8 ^ GMUL2[c] ^ GMUL3[d];
+      self.mat[3][j] = GMUL3[a] ^ b as u8 ^ c as u8 ^ GMUL2[d];
+    }
     self
   }
 
@@ -145,10 +131,59 @@ impl AESBlock {
     if ignore {
       return self;
     }
-    self.mat = Self::matrix_gmult(&Self::INV_MIX_COLUMN_CT, self);
+    for j in 0..4 {
+      let [a, b, c, d] = [0, 1, 2, 3].map(|i| self.mat[i][j] as usize);
+      self.mat[0][j] = GMUL14[a] ^ GMUL11[b] ^ GMUL13[c] ^ GMUL9[d];
+      self.mat[1][j] = GMUL9[a] ^ GMUL14[b] ^ GMUL11[c] ^ GMUL13[d];
+      self.mat[2][j] = GMUL13[a] ^ GMUL9[b] ^ GMUL14[c] ^ GMUL11[d];
+      self.mat[3][j] = GMUL11[a] ^ GMUL13[b] ^ GMUL9[c] ^ GMUL14[d];
+    }
     self
   }
 
+  // Non-mutating versions of the round steps, to keep every intermediate state as its own value
+  pub fn with_add_round_key(&self, key: &AESKey) -> Self {
+    let mut block = *self;
+    block.add_round_key(key);
+    block
+  }
+
+  pub fn with_sub_bytes(&self) -> Self {
+    let mut block = *self;
+    block.sub_bytes();
+    block
+  }
+
+  pub fn with_shift_rows(&self) -> Self {
+    let mut block = *self;
+    block.shift_rows();
+    block
+  }
+
+  pub fn with_mix_columns(&self) -> Self {
+    let mut block = *self;
+    block.mix_columns(false);
+    block
+  }
+
+  pub fn with_inv_sub_bytes(&self) -> Self {
+    let mut block = *self;
+    block.inv_sub_bytes();
+    block
+  }
+
+  pub fn with_inv_shift_rows(&self) -> Self {
+    let mut block = *self;
+    block.inv_shift_rows();
+    block
+  }
+
+  pub fn with_inv_mix_columns(&self) -> Self {
+    let mut block = *self;
+    block.inv_mix_columns(false);
+    block
+  }
+
   pub fn apply_round(&mut self, round_key: &AESKey, last_round: bool) -> &mut Self {
     self
       .sub_bytes()
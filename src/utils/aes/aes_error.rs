@@ -9,6 +9,11 @@ pub enum AESError {
   PaddingError,
   ConversionError(ConversionError),
   AsciiError(Vec<u8>),
+  UnknownMode(String),
+  NonceReused(u64),
+  InvalidNonceSize(usize, usize),
+  InvalidCounterSize(usize),
+  AuthenticationFailed,
   UnexpectedError,
 }
 
@@ -49,6 +54,21 @@ impl fmt::Display for AESError {
         let hex = HexString::try_from(plaintext.clone()).unwrap();
         write!(f, "ASCII error for obtained plaintext {hex}")
       },
+      Self::UnknownMode(mode) => {
+        write!(f, "Unknown AES mode {mode}. Valid modes are ECB, CBC, CTR, CFB, OFB and GCM.")
+      }
+      Self::NonceReused(nonce) => {
+        write!(f, "Nonce {nonce} was already used with this key.")
+      }
+      Self::InvalidNonceSize(nonce_size, expected_size) => {
+        write!(f, "Nonce of {nonce_size} bytes, this counter layout needs {expected_size}.")
+      }
+      Self::InvalidCounterSize(counter_bytes) => {
+        write!(f, "The CTR counter must take between 1 and 8 bytes, not {counter_bytes}.")
+      }
+      Self::AuthenticationFailed => {
+        write!(f, "The authentication tag does not match the ciphertext.")
+      }
       Self::UnexpectedError => {
         write!(f, "An unexpected error occurred during AES execution.")
       }
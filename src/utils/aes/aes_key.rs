@@ -2,7 +2,12 @@ use core::fmt;
 use rand::Rng;
 
 use crate::utils::conversion::hex_string::HexString;
-use super::{aes_block::AESBlock, aes_error::AESError, constants::*};
+use super::{
+  aes_block::AESBlock,
+  aes_error::AESError,
+  constants::*,
+  utils::{sub_word, word_modifier},
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum AESKey {
@@ -53,6 +58,53 @@ impl AESKey {
     }
   }
 
+  pub fn rounds(&self) -> usize {
+    match self {
+      Self::AES128Key(_) => AES128_ROUNDS,
+      Self::AES192Key(_) => AES192_ROUNDS,
+      Self::AES256Key(_) => AES256_ROUNDS,
+    }
+  }
+
+  // Key expansion of FIPS-197 (section 5.2) for the three key sizes.
+  // Returns the rounds() + 1 round keys, the first one being the key itself (for AES-128)
+  // or its first 16 bytes (for AES-192 and AES-256)
+  pub fn expand_key(&self) -> Vec<AESBlock> {
+    #[cfg(feature = "instrument")]
+    super::instrument::count_key_expansion();
+    let nk = self.size() / 4; // words-per-key
+    let total_words = 4 * (self.rounds() + 1);
+    let mut words: Vec<(u8, u8, u8, u8)> = self
+      .get_array()
+      .chunks(4)
+      .map(|w| (w[0], w[1], w[2], w[3]))
+      .collect();
+
+    for i in nk..total_words {
+      let mut temp = words[i - 1]; // previous word
+      if i % nk == 0 {
+        temp = word_modifier(temp, (i / nk) as u8);
+      } else if nk > 6 && i % nk == 4 {
+        temp = sub_word(temp);
+      }
+      let previous = words[i - nk];
+      words.push((
+        previous.0 ^ temp.0,
+        previous.1 ^ temp.1,
+        previous.2 ^ temp.2,
+        previous.3 ^ temp.3,
+      ));
+    }
+
+    words
+      .chunks(4)
+      .map(|group| {
+        let arr: Vec<u8> = group.iter().flat_map(|w| [w.0, w.1, w.2, w.3]).collect();
+        AESBlock::from_flat_array(&arr.try_into().unwrap())
+      })
+      .collect()
+  }
+
   pub fn get_array(&self) -> &[u8] {
     match self {
       Self::AES128Key(arr) => arr,
@@ -61,25 +113,20 @@ impl AESKey {
     }
   }
 
+  // The first 16 bytes of the key, which are also its first round key for every key size
   pub fn as_block(&self) -> AESBlock {
-    match self {
-      Self::AES128Key(arr) => AESBlock::from_flat_array(arr),
-      Self::AES192Key(_) => unimplemented!(),
-      Self::AES256Key(_) => unimplemented!(),
-    }
+    AESBlock::from_flat_array(self.get_array()[..AES_BLOCK_SIZE].try_into().unwrap())
   }
 
+  // The four words of as_block
   pub fn divide_in_words(&self) -> [(u8, u8, u8, u8); 4] {
-    match self {
-      Self::AES128Key(arr) => [
-        (arr[0], arr[1], arr[2], arr[3]),
-        (arr[4], arr[5], arr[6], arr[7]),
-        (arr[8], arr[9], arr[10], arr[11]),
-        (arr[12], arr[13], arr[14], arr[15]),
-      ],
-      Self::AES192Key(_) => unimplemented!(),
-      Self::AES256Key(_) => unimplemented!(),
-    }
+    let arr = self.get_array();
+    [
+      (arr[0], arr[1], arr[2], arr[3]),
+      (arr[4], arr[5], arr[6], arr[7]),
+      (arr[8], arr[9], arr[10], arr[11]),
+      (arr[12], arr[13], arr[14], arr[15]),
+    ]
   }
 
   pub fn random_key() -> Self {
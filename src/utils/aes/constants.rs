@@ -2,6 +2,8 @@ pub const AES_BLOCK_SIZE: usize = 16;
 pub const AES_BLOCK_ROW_SIZE: usize = 4;
 pub const AES_BLOCK_COL_SIZE: usize = 4;
 pub const AES128_ROUNDS: usize = 10;
+pub const AES192_ROUNDS: usize = 12;
+pub const AES256_ROUNDS: usize = 14;
 pub const AES128_KEY_SIZE: usize = 16;
 pub const AES192_KEY_SIZE: usize = 24;
 pub const AES256_KEY_SIZE: usize = 32;
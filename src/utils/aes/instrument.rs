@@ -0,0 +1,81 @@
+// Counters of AES block operations and key expansions, only compiled with the "instrument" feature.
+// They are per thread, so that parallel tests do not see each other's operations
+use std::cell::Cell;
+
+use super::aes::AES;
+
+thread_local! {
+  static BLOCK_OPERATIONS: Cell<u64> = const { Cell::new(0) };
+  static KEY_EXPANSIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+pub fn count_block_operation() {
+  BLOCK_OPERATIONS.with(|count| count.set(count.get() + 1));
+}
+
+pub fn count_key_expansion() {
+  KEY_EXPANSIONS.with(|count| count.set(count.get() + 1));
+}
+
+// Reads how many operations the current thread did since the counter was created, see AES::with_counter
+pub struct AesCounter {
+  blocks_start: u64,
+  expansions_start: u64,
+}
+
+impl AesCounter {
+  pub fn block_operations(&self) -> u64 {
+    BLOCK_OPERATIONS.with(Cell::get) - self.blocks_start
+  }
+
+  pub fn key_expansions(&self) -> u64 {
+    KEY_EXPANSIONS.with(Cell::get) - self.expansions_start
+  }
+}
+
+impl AES {
+  pub fn with_counter() -> AesCounter {
+    AesCounter {
+      blocks_start: BLOCK_OPERATIONS.with(Cell::get),
+      expansions_start: KEY_EXPANSIONS.with(Cell::get),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::aes::{stream_cipher::AesStreamCipher, utils::AESMode};
+
+  #[test]
+  fn test_counter_increments_once_per_block() {
+    let key = b"YELLOW SUBMARINE";
+    let plaintext = [0x42; 64];
+    let counter = AES::with_counter();
+    let ciphertext = AES::encode_ecb_nopad(&plaintext, key).unwrap();
+    assert_eq!(counter.block_operations(), 4);
+    AES::decode_no_unpad(&ciphertext, key, AESMode::ECB).unwrap();
+    assert_eq!(counter.block_operations(), 8);
+    assert_eq!(counter.key_expansions(), 2);
+  }
+
+  #[test]
+  fn test_cached_schedule_expands_key_once() {
+    let key = b"YELLOW SUBMARINE";
+    let counter = AES::with_counter();
+    for _ in 0..10 {
+      AES::encode(&[0x42; 16], key, AESMode::ECB).unwrap();
+    }
+    assert_eq!(counter.key_expansions(), 10);
+
+    // The stream cipher keeps the round keys for every chunk
+    let counter = AES::with_counter();
+    let mut cipher = AesStreamCipher::new(key, AESMode::CBC([0; 16])).unwrap();
+    for _ in 0..10 {
+      cipher.update(&[0x42; 16]);
+    }
+    cipher.finalize().unwrap();
+    assert_eq!(counter.key_expansions(), 1);
+    assert_eq!(counter.block_operations(), 11);
+  }
+}
@@ -3,4 +3,9 @@ pub mod aes_block;
 pub mod aes_key;
 pub mod aes_error;
 pub mod constants;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod oracle;
+pub mod nonce_tracking_cipher;
+pub mod stream_cipher;
+#[cfg(feature = "instrument")]
+pub mod instrument;
\ No newline at end of file
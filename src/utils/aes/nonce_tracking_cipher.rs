@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+
+use super::{aes::AES, aes_error::AESError, aes_key::AESKey, utils::AESMode};
+
+// Wraps the stateless AES for a fixed key and refuses to encrypt twice with the same CTR nonce.
+// Reusing a nonce reuses the keystream, which is what breaks challenges 19, 20 and 25
+pub struct NonceTrackingCipher {
+  key: AESKey,
+  used_nonces: HashSet<u64>,
+}
+
+impl NonceTrackingCipher {
+  pub fn new<S: AsRef<[u8]>>(key_bytes: &S) -> Result<Self, AESError> {
+    Ok(Self {
+      key: AESKey::from_bytes(key_bytes)?,
+      used_nonces: HashSet::new(),
+    })
+  }
+
+  pub fn encrypt<S: AsRef<[u8]>>(&mut self, plaintext: &S, mode: AESMode) -> Result<Vec<u8>, AESError> {
+    if let AESMode::CTR(nonce) = mode {
+      if !self.used_nonces.insert(nonce) {
+        return Err(AESError::NonceReused(nonce));
+      }
+    }
+    AES::encode(plaintext, &self.key, mode)
+  }
+
+  // Decrypting does not consume the nonce, the same ciphertext can be decrypted many times
+  pub fn decrypt<S: AsRef<[u8]>>(&self, ciphertext: &S, mode: AESMode) -> Result<Vec<u8>, AESError> {
+    AES::decode(ciphertext, &self.key, mode)
+  }
+
+  pub fn is_nonce_used(&self, nonce: u64) -> bool {
+    self.used_nonces.contains(&nonce)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_nonce_reuse_is_rejected() {
+    let mut cipher = NonceTrackingCipher::new(b"YELLOW SUBMARINE").unwrap();
+    let plaintext = b"BOCA YO TE AMO";
+    let ciphertext = cipher.encrypt(plaintext, AESMode::CTR(7)).unwrap();
+    assert!(cipher.is_nonce_used(7));
+    assert_eq!(
+      cipher.encrypt(b"OTRO MENSAJE", AESMode::CTR(7)),
+      Err(AESError::NonceReused(7))
+    );
+    assert_eq!(cipher.decrypt(&ciphertext, AESMode::CTR(7)).unwrap(), plaintext);
+    assert_eq!(cipher.decrypt(&ciphertext, AESMode::CTR(7)).unwrap(), plaintext);
+  }
+
+  #[test]
+  fn test_different_nonces_are_accepted() {
+    let mut cipher = NonceTrackingCipher::new(b"YELLOW SUBMARINE").unwrap();
+    let plaintext = b"BOCA YO TE AMO";
+    let ciphertext1 = cipher.encrypt(plaintext, AESMode::CTR(0)).unwrap();
+    let ciphertext2 = cipher.encrypt(plaintext, AESMode::CTR(1)).unwrap();
+    assert_ne!(ciphertext1, ciphertext2);
+    assert!(!cipher.is_nonce_used(2));
+    // Modes without a nonce are not tracked
+    cipher.encrypt(plaintext, AESMode::ECB).unwrap();
+    cipher.encrypt(plaintext, AESMode::ECB).unwrap();
+  }
+}
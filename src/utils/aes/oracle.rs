@@ -0,0 +1,194 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use super::{aes::AES, constants::AES_BLOCK_SIZE, utils::AESMode};
+
+// Challenge 11 oracle: adds 5-10 random bytes at each side and encrypts under a random key with ECB or CBC.
+// The mode used in the last encryption is kept so the detection can be checked
+pub struct RandomModeOracle<R: Rng> {
+  rng: R,
+  last_mode: Option<AESMode>,
+}
+
+impl<R: Rng> RandomModeOracle<R> {
+  pub fn new(rng: R) -> Self {
+    Self {
+      rng,
+      last_mode: None,
+    }
+  }
+
+  pub fn last_mode(&self) -> Option<&AESMode> {
+    self.last_mode.as_ref()
+  }
+
+  pub fn encrypt<S: AsRef<[u8]>>(&mut self, plaintext: &S) -> Vec<u8> {
+    let (pre_len, post_len) = (self.rng.gen_range(5..=10), self.rng.gen_range(5..=10));
+    let pre_bytes: Vec<u8> = (0..pre_len).map(|_| self.rng.gen()).collect();
+    let post_bytes: Vec<u8> = (0..post_len).map(|_| self.rng.gen()).collect();
+    let text = [pre_bytes, plaintext.as_ref().to_vec(), post_bytes].concat();
+
+    let key: [u8; 16] = self.rng.gen();
+    let mode = if self.rng.gen_bool(0.5) {
+      AESMode::ECB
+    } else {
+      AESMode::CBC(self.rng.gen())
+    };
+    self.last_mode = Some(mode.clone());
+    AES::encode(&text, &key, mode).unwrap()
+  }
+}
+
+// The two modes the Challenge 11 oracle chooses from. Only the mode is detected, not the IV of CBC
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DetectedMode {
+  Ecb,
+  Cbc,
+}
+
+impl DetectedMode {
+  // The kind of an AES mode, None for the modes the detection does not tell apart
+  pub fn of(mode: &AESMode) -> Option<Self> {
+    match mode {
+      AESMode::ECB => Some(Self::Ecb),
+      AESMode::CBC(_) => Some(Self::Cbc),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for DetectedMode {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Ecb => write!(f, "ECB"),
+      Self::Cbc => write!(f, "CBC"),
+    }
+  }
+}
+
+// ECB encrypts equal blocks to equal blocks. Feed the oracle at least three equal blocks so two of them are aligned
+pub fn classify_mode<S: AsRef<[u8]>>(ciphertext: &S) -> DetectedMode {
+  let mut seen_blocks: HashSet<&[u8]> = HashSet::new();
+  let has_repeated_block = ciphertext
+    .as_ref()
+    .chunks(AES_BLOCK_SIZE)
+    .any(|block| !seen_blocks.insert(block));
+  if has_repeated_block {
+    DetectedMode::Ecb
+  } else {
+    DetectedMode::Cbc
+  }
+}
+
+// Length of the suffix an oracle with PKCS#7 padding appends after the prefix and our input. The ciphertext grows
+// by a block when the plaintext reaches a multiple of block_size
+fn suffix_len(oracle: &impl Fn(&[u8]) -> Vec<u8>, block_size: usize, pad_len: usize, skip: usize) -> usize {
+  let initial_len = oracle(&vec![b'A'; pad_len]).len();
+  let extra = (1..=block_size)
+    .find(|&extra| oracle(&vec![b'A'; pad_len + extra]).len() > initial_len)
+    .unwrap_or(block_size);
+  (initial_len - skip).saturating_sub(extra)
+}
+
+/*
+  Byte-at-a-time ECB decryption (Challenges 12 and 14) of the unknown suffix the oracle appends to our input.
+  The prefix is completed to a whole block, and the filler is chosen so that the block being attacked has
+  block_size - 1 known bytes followed by the next unknown byte. Its ciphertext is then compared against the
+  256 possible blocks. The length of the suffix is known from the ciphertext lengths, so the padding is never read
+*/
+pub fn byte_at_a_time_ecb(oracle: &impl Fn(&[u8]) -> Vec<u8>, block_size: usize, prefix_len: usize) -> Vec<u8> {
+  let pad_len = (block_size - prefix_len % block_size) % block_size;
+  let skip = prefix_len + pad_len;
+  let suffix_len = suffix_len(oracle, block_size, pad_len, skip);
+  let mut recovered: Vec<u8> = vec![];
+  while recovered.len() < suffix_len {
+    let i = recovered.len();
+    let filler = vec![b'A'; pad_len + block_size - 1 - i % block_size];
+    let ciphertext = oracle(&filler);
+    let start = skip + (i / block_size) * block_size;
+    if start + block_size > ciphertext.len() {
+      break;
+    }
+    let target = &ciphertext[start..start + block_size];
+
+    let known = [vec![b'A'; block_size - 1], recovered.clone()].concat();
+    let window = &known[known.len() - (block_size - 1)..];
+    let found = (0..=255u8).find(|&byte| {
+      let input = [vec![b'A'; pad_len].as_slice(), window, &[byte]].concat();
+      oracle(&input)[skip..skip + block_size] == *target
+    });
+    match found {
+      Some(byte) => recovered.push(byte),
+      None => break,
+    }
+  }
+  recovered
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::oracle::detect_prefix_len;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  #[test]
+  fn test_classify_mode_of_random_mode_oracle() {
+    let mut oracle = RandomModeOracle::new(StdRng::seed_from_u64(11));
+    let plaintext = [b'A'; 3 * AES_BLOCK_SIZE];
+    let (mut ecb_count, trials) = (0, 200);
+    for _ in 0..trials {
+      let ciphertext = oracle.encrypt(&plaintext);
+      let true_mode = oracle.last_mode().unwrap();
+      if *true_mode == AESMode::ECB {
+        ecb_count += 1;
+      }
+      assert_eq!(Some(classify_mode(&ciphertext)), DetectedMode::of(true_mode));
+    }
+    assert!(ecb_count > 0 && ecb_count < trials);
+  }
+
+  #[test]
+  fn test_random_mode_oracle_is_deterministic() {
+    let mut oracle1 = RandomModeOracle::new(StdRng::seed_from_u64(42));
+    let mut oracle2 = RandomModeOracle::new(StdRng::seed_from_u64(42));
+    assert!(oracle1.last_mode().is_none());
+    for _ in 0..10 {
+      assert_eq!(oracle1.encrypt(b"YELLOW SUBMARINE"), oracle2.encrypt(b"YELLOW SUBMARINE"));
+      assert_eq!(oracle1.last_mode(), oracle2.last_mode());
+    }
+  }
+
+  const SUFFIX: &[u8] = b"Rollin' in my 5.0\nWith my rag-top down\n";
+
+  #[test]
+  fn test_byte_at_a_time_ecb_without_prefix() {
+    let key: [u8; 16] = StdRng::seed_from_u64(12).gen();
+    let oracle = |input: &[u8]| AES::encode(&[input, SUFFIX].concat(), &key, AESMode::ECB).unwrap();
+    assert_eq!(detect_prefix_len(&oracle, AES_BLOCK_SIZE), 0);
+    assert_eq!(byte_at_a_time_ecb(&oracle, AES_BLOCK_SIZE, 0), SUFFIX);
+  }
+
+  #[test]
+  fn test_byte_at_a_time_ecb_with_random_prefix() {
+    let mut rng = StdRng::seed_from_u64(14);
+    let key: [u8; 16] = rng.gen();
+    for prefix_len in [5, 16, 37] {
+      let prefix: Vec<u8> = (0..prefix_len).map(|_| rng.gen()).collect();
+      let oracle =
+        |input: &[u8]| AES::encode(&[prefix.as_slice(), input, SUFFIX].concat(), &key, AESMode::ECB).unwrap();
+      assert_eq!(detect_prefix_len(&oracle, AES_BLOCK_SIZE), prefix_len);
+      assert_eq!(byte_at_a_time_ecb(&oracle, AES_BLOCK_SIZE, prefix_len), SUFFIX);
+    }
+  }
+
+  #[test]
+  fn test_byte_at_a_time_ecb_keeps_trailing_0x01() {
+    let key: [u8; 16] = StdRng::seed_from_u64(16).gen();
+    for suffix in [&b"ends with one\x01"[..], b"\x01", b"0123456789abcdef\x01\x01"] {
+      let oracle = |input: &[u8]| AES::encode(&[input, suffix].concat(), &key, AESMode::ECB).unwrap();
+      assert_eq!(byte_at_a_time_ecb(&oracle, AES_BLOCK_SIZE, 0), suffix);
+    }
+  }
+}
@@ -0,0 +1,150 @@
+use super::{
+  aes::AES,
+  aes_block::AESBlock,
+  aes_error::AESError,
+  aes_key::AESKey,
+  constants::AES_BLOCK_SIZE,
+  utils::{pkcs_padding, AESMode, CtrConfig},
+};
+
+// Encrypts a plaintext fed in chunks of any size, giving the same result as AES::encode.
+// Only full blocks are encrypted in update, the rest waits in buf for the next chunk or for finalize,
+// which pads it in ECB and CBC. GCM is not supported because its tag needs the whole ciphertext
+pub struct AesStreamCipher {
+  round_keys: Vec<AESKey>,
+  mode: AESMode,
+  buf: Vec<u8>,
+  // Previous ciphertext block in CBC and CFB, previous keystream block in OFB
+  chain: AESBlock,
+  blocks_processed: u64,
+}
+
+impl AesStreamCipher {
+  pub fn new<S: AsRef<[u8]>>(key_bytes: &S, mode: AESMode) -> Result<Self, AESError> {
+    let aes = AES::create_from(key_bytes, mode.clone())?;
+    let chain = match mode {
+      AESMode::CBC(iv) | AESMode::CFB(iv) | AESMode::OFB(iv) => AESBlock::from_flat_array(&iv),
+      AESMode::CTRWithConfig { ref nonce, config } => {
+        config.validate(nonce)?;
+        AESBlock::from_flat_array(&[0; AES_BLOCK_SIZE])
+      }
+      AESMode::GCM { .. } => return Err(AESError::UnknownMode(mode.to_string())),
+      _ => AESBlock::from_flat_array(&[0; AES_BLOCK_SIZE]),
+    };
+    Ok(Self {
+      round_keys: aes.get_round_keys(),
+      mode,
+      buf: Vec::new(),
+      chain,
+      blocks_processed: 0,
+    })
+  }
+
+  pub fn update<S: AsRef<[u8]>>(&mut self, chunk: &S) -> Vec<u8> {
+    self.buf.extend_from_slice(chunk.as_ref());
+    let full_len = self.buf.len() - self.buf.len() % AES_BLOCK_SIZE;
+    let full_blocks: Vec<u8> = self.buf.drain(..full_len).collect();
+    full_blocks.chunks(AES_BLOCK_SIZE).flat_map(|block| self.process(block)).collect()
+  }
+
+  pub fn finalize(&mut self) -> Result<Vec<u8>, AESError> {
+    let rest = std::mem::take(&mut self.buf);
+    match self.mode {
+      AESMode::ECB | AESMode::CBC(_) => Ok(self.process(&pkcs_padding(&rest, AES_BLOCK_SIZE as u8))),
+      // Stream modes encrypt the last partial block without padding
+      _ if rest.is_empty() => Ok(vec![]),
+      _ => Ok(self.process(&rest)),
+    }
+  }
+
+  // Encrypts one block, which can only be partial at the end of a stream mode
+  fn process(&mut self, block: &[u8]) -> Vec<u8> {
+    let result: Vec<u8> = match self.mode {
+      AESMode::ECB => self.encrypt(&Self::to_block(block)).to_vec(),
+      AESMode::CBC(_) => {
+        let mut input = Self::to_block(block);
+        input.xor_with_block(&self.chain);
+        let output = self.encrypt(&input);
+        self.chain = AESBlock::from_flat_array(&output);
+        output.to_vec()
+      }
+      AESMode::CTR(nonce) => {
+        let counter = CtrConfig::default().counter_block(&nonce.to_le_bytes(), self.blocks_processed);
+        Self::xor(block, &self.encrypt(&AESBlock::from_flat_array(&counter)))
+      }
+      AESMode::CTRWithConfig { ref nonce, config } => {
+        let counter = config.counter_block(nonce, self.blocks_processed);
+        Self::xor(block, &self.encrypt(&AESBlock::from_flat_array(&counter)))
+      }
+      AESMode::CFB(_) => {
+        let output = Self::xor(block, &self.encrypt(&self.chain));
+        if output.len() == AES_BLOCK_SIZE {
+          self.chain = Self::to_block(&output);
+        }
+        output
+      }
+      AESMode::OFB(_) => {
+        let keystream = self.encrypt(&self.chain);
+        self.chain = AESBlock::from_flat_array(&keystream);
+        Self::xor(block, &keystream)
+      }
+      AESMode::GCM { .. } => unreachable!("Rejected in AesStreamCipher::new"),
+    };
+    self.blocks_processed += 1;
+    result
+  }
+
+  fn encrypt(&self, block: &AESBlock) -> [u8; AES_BLOCK_SIZE] {
+    let mut block = *block;
+    AES::encrypt_block(&mut block, &self.round_keys);
+    block.as_flatten_array()
+  }
+
+  fn to_block(bytes: &[u8]) -> AESBlock {
+    AESBlock::from_flat_array(&bytes.try_into().unwrap())
+  }
+
+  fn xor(bytes: &[u8], keystream: &[u8; AES_BLOCK_SIZE]) -> Vec<u8> {
+    bytes.iter().zip(keystream).map(|(b, k)| b ^ k).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stream_cipher_matches_encode() {
+    let key = b"YELLOW SUBMARINE";
+    let plaintext: Vec<u8> = (0..100u8).collect();
+    let modes = [
+      AESMode::ECB,
+      AESMode::CBC([1; 16]),
+      AESMode::CTR(5),
+      AESMode::CTRWithConfig { nonce: vec![2; 12], config: CtrConfig::nist(7) },
+      AESMode::CFB([3; 16]),
+      AESMode::OFB([4; 16]),
+    ];
+    for mode in modes {
+      for length in [0, 15, 16, 100] {
+        let mut cipher = AesStreamCipher::new(key, mode.clone()).unwrap();
+        let mut ciphertext: Vec<u8> = plaintext[..length].chunks(7).flat_map(|chunk| cipher.update(&chunk)).collect();
+        ciphertext.extend(cipher.finalize().unwrap());
+        assert_eq!(ciphertext, AES::encode(&plaintext[..length].to_vec(), key, mode.clone()).unwrap(), "{mode} {length}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_stream_cipher_outputs_only_full_blocks() {
+    let mut cipher = AesStreamCipher::new(b"YELLOW SUBMARINE", AESMode::CBC([0; 16])).unwrap();
+    assert!(cipher.update(b"fifteen bytes..").is_empty());
+    assert_eq!(cipher.update(b"!").len(), 16);
+    assert_eq!(cipher.finalize().unwrap().len(), 16);
+    let gcm = AESMode::GCM { nonce: [0; 12], aad: vec![] };
+    assert_eq!(
+      AesStreamCipher::new(b"YELLOW SUBMARINE", gcm).err(),
+      Some(AESError::UnknownMode(String::from("GCM")))
+    );
+  }
+}
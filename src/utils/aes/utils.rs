@@ -1,3 +1,6 @@
+use core::fmt;
+use std::str::FromStr;
+
 use super::{aes_error::AESError, constants::*};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -5,7 +8,121 @@ pub enum AESMode {
   ECB,
   CBC([u8; 16]),
   CTR(u64),
-  GCM,
+  CTRWithConfig { nonce: Vec<u8>, config: CtrConfig },
+  CFB([u8; 16]),
+  OFB([u8; 16]),
+  GCM { nonce: [u8; 12], aad: Vec<u8> },
+}
+
+// Layout of the CTR counter blocks. The nonce takes the 16 - counter_bytes bytes not used by the counter,
+// which wraps around modulo 2^(8 * counter_bytes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CtrConfig {
+  pub counter_bytes: usize,
+  pub big_endian: bool,
+  pub nonce_first: bool,
+  pub initial_counter: u64,
+}
+
+impl CtrConfig {
+  // 96-bit nonce followed by a 32-bit big-endian counter, as in NIST SP 800-38A and OpenSSL
+  pub fn nist(initial_counter: u32) -> Self {
+    Self {
+      counter_bytes: 4,
+      big_endian: true,
+      nonce_first: true,
+      initial_counter: initial_counter as u64,
+    }
+  }
+
+  pub fn nonce_len(&self) -> usize {
+    AES_BLOCK_SIZE.saturating_sub(self.counter_bytes)
+  }
+
+  // The counter is a u64, so it takes between 1 and 8 bytes, and the nonce fills the rest of the block
+  pub fn validate(&self, nonce: &[u8]) -> Result<(), AESError> {
+    if !(1..=8).contains(&self.counter_bytes) {
+      return Err(AESError::InvalidCounterSize(self.counter_bytes));
+    }
+    if nonce.len() != self.nonce_len() {
+      return Err(AESError::InvalidNonceSize(nonce.len(), self.nonce_len()));
+    }
+    Ok(())
+  }
+
+  // Expects a config and nonce accepted by validate
+  pub fn counter_block(&self, nonce: &[u8], block_index: u64) -> [u8; AES_BLOCK_SIZE] {
+    let counter = self.initial_counter.wrapping_add(block_index);
+    let counter_bytes = if self.big_endian {
+      counter.to_be_bytes()[8 - self.counter_bytes..].to_vec()
+    } else {
+      counter.to_le_bytes()[..self.counter_bytes].to_vec()
+    };
+    let block = if self.nonce_first {
+      [nonce, &counter_bytes].concat()
+    } else {
+      [&counter_bytes, nonce].concat()
+    };
+    block.try_into().unwrap()
+  }
+}
+
+// The format of AESMode::CTR: 64-bit nonce and 64-bit little-endian counter starting at 0
+impl Default for CtrConfig {
+  fn default() -> Self {
+    Self {
+      counter_bytes: 8,
+      big_endian: false,
+      nonce_first: true,
+      initial_counter: 0,
+    }
+  }
+}
+
+// The IV / nonce is not part of the name, so it is zero-defaulted. Replace it after parsing if needed
+impl FromStr for AESMode {
+  type Err = AESError;
+  fn from_str(s: &str) -> Result<Self, AESError> {
+    match s.trim().to_ascii_lowercase().as_str() {
+      "ecb" => Ok(Self::ECB),
+      "cbc" => Ok(Self::CBC([0; 16])),
+      "ctr" => Ok(Self::CTR(0)),
+      "cfb" => Ok(Self::CFB([0; 16])),
+      "ofb" => Ok(Self::OFB([0; 16])),
+      "gcm" => Ok(Self::GCM { nonce: [0; 12], aad: vec![] }),
+      _ => Err(AESError::UnknownMode(s.to_string())),
+    }
+  }
+}
+
+impl fmt::Display for AESMode {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::ECB => write!(f, "ECB"),
+      Self::CBC(_) => write!(f, "CBC"),
+      Self::CTR(_) | Self::CTRWithConfig { .. } => write!(f, "CTR"),
+      Self::CFB(_) => write!(f, "CFB"),
+      Self::OFB(_) => write!(f, "OFB"),
+      Self::GCM { .. } => write!(f, "GCM"),
+    }
+  }
+}
+
+// Compares every byte regardless of where the first difference is, so the time does not leak it
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  if a.len() != b.len() {
+    return false;
+  }
+  a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn sub_word(word: (u8, u8, u8, u8)) -> (u8, u8, u8, u8) {
+  (
+    S_BOX[word.0 as usize],
+    S_BOX[word.1 as usize],
+    S_BOX[word.2 as usize],
+    S_BOX[word.3 as usize],
+  )
 }
 
 pub fn word_modifier(word: (u8, u8, u8, u8), round: u8) -> (u8, u8, u8, u8) {
@@ -27,16 +144,13 @@ pub fn word_modifier(word: (u8, u8, u8, u8), round: u8) -> (u8, u8, u8, u8) {
   temp
 }
 
+// PKCS#7 always adds padding, a whole block of it when the input is already aligned.
+// Otherwise a plaintext ending in something like 0x01 could not be told apart from its padding
 pub fn pkcs_padding<S: AsRef<[u8]>>(bytes: &S, final_length: u8) -> Vec<u8> {
-  let text_length = bytes.as_ref().len() as u8;
+  let text_length = bytes.as_ref().len();
   let mut vec = bytes.as_ref().to_vec();
-  let remainder = text_length % final_length;
-  if remainder != 0 {
-    let diff: u8 = final_length - remainder;
-    for _ in 0..diff {
-      vec.push(diff);
-    }
-  }
+  let diff = final_length - (text_length % final_length as usize) as u8;
+  vec.resize(text_length + diff as usize, diff);
   vec
 }
 
@@ -66,6 +180,13 @@ pub fn has_valid_pkcs_padding<S: AsRef<[u8]>>(bytes: &S, block_size: u8) -> Resu
   Ok(())
 }
 
+pub fn strip_pkcs_padding<S: AsRef<[u8]>>(bytes: &S, block_size: u8) -> Result<Vec<u8>, AESError> {
+  has_valid_pkcs_padding(bytes, block_size)?;
+  let bytes = bytes.as_ref();
+  let padding_len = bytes[bytes.len() - 1] as usize;
+  Ok(bytes[..bytes.len() - padding_len].to_vec())
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -85,6 +206,9 @@ mod tests {
       pkcs_padding(block, padding),
       b"YELLOW SUBMARINE\x04\x04\x04\x04"
     );
+    let padded = pkcs_padding(block, AES_BLOCK_SIZE as u8);
+    assert_eq!(padded, [block.as_slice(), &[16; 16]].concat());
+    assert_eq!(strip_pkcs_padding(&padded, AES_BLOCK_SIZE as u8).unwrap(), block);
   }
 
   #[test]
@@ -106,4 +230,26 @@ mod tests {
       Err(AESError::PaddingError)
     );
   }
+
+  #[test]
+  fn test_aes_mode_from_str() {
+    assert_eq!("ecb".parse::<AESMode>(), Ok(AESMode::ECB));
+    assert_eq!("CBC".parse::<AESMode>(), Ok(AESMode::CBC([0; 16])));
+    assert_eq!(" ctr ".parse::<AESMode>(), Ok(AESMode::CTR(0)));
+    assert_eq!("cfb".parse::<AESMode>(), Ok(AESMode::CFB([0; 16])));
+    assert_eq!("OFB".parse::<AESMode>(), Ok(AESMode::OFB([0; 16])));
+    assert_eq!("Gcm".parse::<AESMode>(), Ok(AESMode::GCM { nonce: [0; 12], aad: vec![] }));
+    let gcm = AESMode::GCM { nonce: [0; 12], aad: vec![] };
+    for mode in [AESMode::ECB, AESMode::CBC([0; 16]), AESMode::CTR(0), AESMode::CFB([0; 16]), AESMode::OFB([0; 16]), gcm] {
+      assert_eq!(mode.to_string().parse::<AESMode>(), Ok(mode));
+    }
+  }
+
+  #[test]
+  fn test_aes_mode_from_invalid_str() {
+    assert_eq!(
+      "pcbc".parse::<AESMode>(),
+      Err(AESError::UnknownMode(String::from("pcbc")))
+    );
+  }
 }
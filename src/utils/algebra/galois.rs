@@ -1,7 +1,8 @@
-pub fn galois_multiplication(x: u8, y: u8) -> u8 {
+pub const fn galois_multiplication(x: u8, y: u8) -> u8 {
   let mut p = 0u8;
   let (mut a, mut b) = (x, y); // mutable copies of x,y
-  for _ in 0..8 {
+  let mut i = 0;
+  while i < 8 {
     if b & 1 != 0 {
       p ^= a;
     }
@@ -11,6 +12,50 @@ pub fn galois_multiplication(x: u8, y: u8) -> u8 {
       a ^= 0x1b; // Reduce modulo the irreducible polynomial x^8 + x^4 + x^3 + x^2 + 1
     }
     b >>= 1;
+    i += 1;
   }
   p
-}
\ No newline at end of file
+}
+
+// Table with the product of every byte by a fixed factor, built at compile time
+const fn gmul_table(factor: u8) -> [u8; 256] {
+  let mut table = [0u8; 256];
+  let mut i = 0;
+  while i < 256 {
+    table[i] = galois_multiplication(i as u8, factor);
+    i += 1;
+  }
+  table
+}
+
+// Factors used by MixColumns and InvMixColumns
+pub const GMUL2: [u8; 256] = gmul_table(0x02);
+pub const GMUL3: [u8; 256] = gmul_table(0x03);
+pub const GMUL9: [u8; 256] = gmul_table(0x09);
+pub const GMUL11: [u8; 256] = gmul_table(0x0b);
+pub const GMUL13: [u8; 256] = gmul_table(0x0d);
+pub const GMUL14: [u8; 256] = gmul_table(0x0e);
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::aes::aes_block::AESBlock;
+
+  // Known MixColumns vectors, one column each
+  const COLUMNS: [([u8; 4], [u8; 4]); 6] = [
+    ([0xdb, 0x13, 0x53, 0x45], [0x8e, 0x4d, 0xa1, 0xbc]),
+    ([0xf2, 0x0a, 0x22, 0x5c], [0x9f, 0xdc, 0x58, 0x9d]),
+    ([0x01, 0x01, 0x01, 0x01], [0x01, 0x01, 0x01, 0x01]),
+    ([0xc6, 0xc6, 0xc6, 0xc6], [0xc6, 0xc6, 0xc6, 0xc6]),
+    ([0xd4, 0xd4, 0xd4, 0xd5], [0xd5, 0xd5, 0xd7, 0xd6]),
+    ([0x2d, 0x26, 0x31, 0x4c], [0x4d, 0x7e, 0xbd, 0xf8]),
+  ];
+
+  #[test]
+  fn test_gmul_tables_mix_columns_vectors() {
+    for (input, output) in COLUMNS {
+      let mut block = AESBlock::from_flat_array(&[input; 4].concat().try_into().unwrap());
+      assert_eq!(block.mix_columns(false).as_flatten_array(), *[output; 4].concat());
+      assert_eq!(block.inv_mix_columns(false).as_flatten_array(), *[input; 4].concat());
+    }
+  }
+}
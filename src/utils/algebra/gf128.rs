@@ -0,0 +1,135 @@
+use std::ops::{Add, Mul};
+
+// R = x^128 + x^7 + x^2 + x + 1 in the bit-reflected order used by GCM
+const GCM_R: u128 = 0xe1 << 120;
+// x^128 = x^7 + x^2 + x + 1 in the little-endian order used by XTS
+const XTS_R: u128 = 0x87;
+
+// An element of GF(2^128). For GCM the value is read from its 16 bytes in big-endian order and the
+// most significant bit is the coefficient of x^0. For XTS it is read in little-endian order and
+// the least significant bit is the coefficient of x^0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Gf128(pub u128);
+
+impl Gf128 {
+  pub fn from_be_bytes(bytes: [u8; 16]) -> Self {
+    Self(u128::from_be_bytes(bytes))
+  }
+
+  pub fn to_be_bytes(self) -> [u8; 16] {
+    self.0.to_be_bytes()
+  }
+
+  pub fn from_le_bytes(bytes: [u8; 16]) -> Self {
+    Self(u128::from_le_bytes(bytes))
+  }
+
+  pub fn to_le_bytes(self) -> [u8; 16] {
+    self.0.to_le_bytes()
+  }
+
+  // Multiplication by x in the GCM convention
+  pub fn mul_x(self) -> Self {
+    if self.0 & 1 == 1 {
+      Self((self.0 >> 1) ^ GCM_R)
+    } else {
+      Self(self.0 >> 1)
+    }
+  }
+
+  // Multiplication by x in the XTS convention, used to get the tweak of the next block
+  pub fn double(self) -> Self {
+    let carry = self.0 >> 127;
+    Self((self.0 << 1) ^ (carry * XTS_R))
+  }
+}
+
+// Addition in characteristic 2 is XOR
+impl Add for Gf128 {
+  type Output = Self;
+  #[allow(clippy::suspicious_arithmetic_impl)]
+  fn add(self, other: Self) -> Self {
+    Self(self.0 ^ other.0)
+  }
+}
+
+// Algorithm 1 of NIST SP 800-38D, the multiplication GHASH and GMAC are built on
+impl Mul for Gf128 {
+  type Output = Self;
+  fn mul(self, other: Self) -> Self {
+    let mut z = 0u128;
+    let mut v = other;
+    for i in (0..128).rev() {
+      if (self.0 >> i) & 1 == 1 {
+        z ^= v.0;
+      }
+      v = v.mul_x();
+    }
+    Self(z)
+  }
+}
+
+// GHASH_H(A || 0* || C || 0* || len(A) || len(C)), with the lengths in bits as 64-bit big-endian integers
+pub fn ghash(h: Gf128, aad: &[u8], ciphertext: &[u8]) -> Gf128 {
+  let mut y = Gf128::default();
+  for data in [aad, ciphertext] {
+    for chunk in data.chunks(16) {
+      let mut block = [0u8; 16];
+      block[..chunk.len()].copy_from_slice(chunk);
+      y = (y + Gf128::from_be_bytes(block)) * h;
+    }
+  }
+  let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+  (y + Gf128(lengths)) * h
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_gcm_multiplication() {
+    // Test case 2 of the GCM specification: X1 = C1 * H and GHASH(H, {}, C) = (X1 + len(A)||len(C)) * H
+    let h = Gf128(0x66e94bd4ef8a2c3b884cfa59ca342b2e);
+    let c = Gf128(0x0388dace60b6a392f328c2b971b2fe78);
+    let x1 = c * h;
+    assert_eq!(x1, Gf128(0x5e2ec746917062882c85b0685353deb7));
+    assert_eq!((x1 + Gf128(0x80)) * h, Gf128(0xf38cbb1ad69223dcc3457ae5b6b0f885));
+  }
+
+  #[test]
+  fn test_ghash() {
+    let h = Gf128(0x66e94bd4ef8a2c3b884cfa59ca342b2e);
+    let c = 0x0388dace60b6a392f328c2b971b2fe78u128.to_be_bytes();
+    assert_eq!(ghash(h, &[], &c), Gf128(0xf38cbb1ad69223dcc3457ae5b6b0f885));
+    assert_eq!(ghash(h, &[], &[]), Gf128::default());
+  }
+
+  #[test]
+  fn test_gcm_multiplication_properties() {
+    let one = Gf128(1 << 127); // x^0 is the most significant bit
+    let a = Gf128(0x0123456789abcdeffedcba9876543210);
+    let b = Gf128(0x66e94bd4ef8a2c3b884cfa59ca342b2e);
+    assert_eq!(a * one, a);
+    assert_eq!(a * b, b * a);
+    assert_eq!(a * Gf128(1 << 126), a.mul_x());
+    assert_eq!(a + a, Gf128::default());
+  }
+
+  #[test]
+  fn test_xts_doubling() {
+    let mut tweak = [0u8; 16];
+    tweak[0] = 0x01;
+    assert_eq!(Gf128::from_le_bytes(tweak).double().to_le_bytes()[0], 0x02);
+    // The carry out of the last byte is reduced into the first one
+    let mut tweak = [0u8; 16];
+    tweak[15] = 0x80;
+    let mut expected = [0u8; 16];
+    expected[0] = 0x87;
+    assert_eq!(Gf128::from_le_bytes(tweak).double().to_le_bytes(), expected);
+    let tweak = Gf128::from_le_bytes([0xff; 16]).double().to_le_bytes();
+    let mut expected = [0xff; 16];
+    expected[0] = 0xfe ^ 0x87;
+    assert_eq!(tweak, expected);
+  }
+}
@@ -0,0 +1,165 @@
+use num::rational::BigRational;
+use num::Integer;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, Zero};
+
+fn dot(a: &[BigRational], b: &[BigRational]) -> BigRational {
+  a.iter().zip(b).fold(BigRational::zero(), |acc, (x, y)| acc + x * y)
+}
+
+fn to_rational(v: &[BigInt]) -> Vec<BigRational> {
+  v.iter().map(|x| BigRational::from_integer(x.clone())).collect()
+}
+
+// Returns the Gram-Schmidt vectors b*_i, their squared norms and the coefficients mu[i][j] = <b_i, b*_j> / <b*_j, b*_j>
+fn gram_schmidt(basis: &[Vec<BigInt>]) -> (Vec<Vec<BigRational>>, Vec<BigRational>, Vec<Vec<BigRational>>) {
+  let n = basis.len();
+  let mut orthogonal: Vec<Vec<BigRational>> = Vec::with_capacity(n);
+  let mut norms: Vec<BigRational> = Vec::with_capacity(n);
+  let mut mu = vec![vec![BigRational::zero(); n]; n];
+  for i in 0..n {
+    let b_i = to_rational(&basis[i]);
+    let mut b_star = b_i.clone();
+    for j in 0..i {
+      mu[i][j] = if norms[j].is_zero() {
+        BigRational::zero()
+      } else {
+        dot(&b_i, &orthogonal[j]) / &norms[j]
+      };
+      for (x, y) in b_star.iter_mut().zip(&orthogonal[j]) {
+        *x -= &mu[i][j] * y;
+      }
+    }
+    norms.push(dot(&b_star, &b_star));
+    orthogonal.push(b_star);
+  }
+  (orthogonal, norms, mu)
+}
+
+fn dot_int(a: &[BigInt], b: &[BigInt]) -> BigInt {
+  a.iter().zip(b).fold(BigInt::zero(), |acc, (x, y)| acc + x * y)
+}
+
+// Nearest integer to a / b for b > 0
+fn round_div(a: &BigInt, b: &BigInt) -> BigInt {
+  (BigInt::from(2) * a + b).div_floor(&(BigInt::from(2) * b))
+}
+
+// LLL reduction with delta = 3/4, in exact integer arithmetic (Cohen, A Course in Computational Algebraic Number Theory,
+// Algorithm 2.6.7). Instead of the Gram-Schmidt coefficients it keeps d_i = prod_{j <= i} |b*_j|^2 and
+// lambda[k][j] = d_j mu[k][j], which are integers. The rows of the basis must be linearly independent.
+// Indices follow the book, starting at 1, with d[0] = 1
+pub fn lll_reduce(basis: &[Vec<BigInt>]) -> Vec<Vec<BigInt>> {
+  let n = basis.len();
+  let mut b: Vec<Vec<BigInt>> = [vec![vec![]], basis.to_vec()].concat();
+  if n < 2 {
+    return basis.to_vec();
+  }
+  let mut d = vec![BigInt::zero(); n + 1];
+  let mut lambda = vec![vec![BigInt::zero(); n + 1]; n + 1];
+  d[0] = BigInt::one();
+  d[1] = dot_int(&b[1], &b[1]);
+  let (mut k, mut k_max) = (2, 1);
+
+  // Size reduction of b_k against b_l
+  let reduce = |b: &mut Vec<Vec<BigInt>>, lambda: &mut Vec<Vec<BigInt>>, d: &[BigInt], k: usize, l: usize| {
+    if BigInt::from(2) * lambda[k][l].abs() <= d[l] {
+      return;
+    }
+    let q = round_div(&lambda[k][l], &d[l]);
+    let b_l = b[l].clone();
+    for (x, y) in b[k].iter_mut().zip(&b_l) {
+      *x -= &q * y;
+    }
+    lambda[k][l] -= &q * &d[l];
+    let lambda_l = lambda[l].clone();
+    for (x, y) in lambda[k][1..l].iter_mut().zip(&lambda_l[1..l]) {
+      *x -= &q * y;
+    }
+  };
+
+  while k <= n {
+    if k > k_max {
+      k_max = k;
+      for j in 1..=k {
+        let mut u = dot_int(&b[k], &b[j]);
+        for i in 1..j {
+          u = (&d[i] * u - &lambda[k][i] * &lambda[j][i]) / &d[i - 1];
+        }
+        if j < k {
+          lambda[k][j] = u;
+        } else {
+          assert!(!u.is_zero(), "The rows of the basis are linearly dependent");
+          d[k] = u;
+        }
+      }
+    }
+    reduce(&mut b, &mut lambda, &d, k, k - 1);
+    // Lovasz condition, d_k d_{k-2} < 3/4 d_{k-1}^2 - lambda_{k,k-1}^2 means b_k and b_{k-1} must be swapped
+    let lhs = BigInt::from(4) * &d[k] * &d[k - 2];
+    let rhs = BigInt::from(3) * &d[k - 1] * &d[k - 1] - BigInt::from(4) * &lambda[k][k - 1] * &lambda[k][k - 1];
+    if lhs < rhs {
+      b.swap(k, k - 1);
+      let (lower, upper) = lambda.split_at_mut(k);
+      lower[k - 1][1..k - 1].swap_with_slice(&mut upper[0][1..k - 1]);
+      let l = lambda[k][k - 1].clone();
+      let new_d = (&d[k - 2] * &d[k] + &l * &l) / &d[k - 1];
+      for row in lambda[k + 1..=k_max].iter_mut() {
+        let t = row[k].clone();
+        row[k] = (&d[k] * &row[k - 1] - &l * &t) / &d[k - 1];
+        row[k - 1] = (&new_d * t + &l * &row[k]) / &d[k];
+      }
+      d[k - 1] = new_d;
+      k = (k - 1).max(2);
+    } else {
+      for l in (1..k - 1).rev() {
+        reduce(&mut b, &mut lambda, &d, k, l);
+      }
+      k += 1;
+    }
+  }
+  b.split_off(1)
+}
+
+pub fn squared_norm(v: &[BigInt]) -> BigInt {
+  v.iter().fold(BigInt::zero(), |acc, x| acc + x * x)
+}
+
+// True if |mu[i][j]| <= 1/2 for j < i and the Lovasz condition holds for every pair of consecutive vectors
+pub fn is_lll_reduced(basis: &[Vec<BigInt>]) -> bool {
+  let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
+  let half = BigRational::new(BigInt::one(), BigInt::from(2));
+  let (_, norms, mu) = gram_schmidt(basis);
+  let size_reduced = (0..basis.len()).all(|i| (0..i).all(|j| mu[i][j].abs() <= half));
+  let lovasz =
+    (1..basis.len()).all(|k| norms[k] >= (&delta - &mu[k][k - 1] * &mu[k][k - 1]) * &norms[k - 1]);
+  size_reduced && lovasz
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn to_basis(rows: &[&[i64]]) -> Vec<Vec<BigInt>> {
+    rows.iter().map(|row| row.iter().map(|&x| BigInt::from(x)).collect()).collect()
+  }
+
+  #[test]
+  fn test_lll_reduce_small_basis() {
+    let basis = to_basis(&[&[1, 1, 1], &[-1, 0, 2], &[3, 5, 6]]);
+    let reduced = lll_reduce(&basis);
+    // The last vector depends on how mu = 1/2 is rounded, (-1, 0, 2) and (-2, 0, 1) are both valid
+    assert_eq!(reduced[..2], to_basis(&[&[0, 1, 0], &[1, 0, 1]]));
+    assert!(is_lll_reduced(&reduced));
+    assert!(!is_lll_reduced(&basis));
+  }
+
+  #[test]
+  fn test_lll_finds_short_vector() {
+    // The lattice spanned by (1, a) and (0, m) contains (k, k * a mod m) for every k, some of them short
+    let basis = to_basis(&[&[1, 123456789], &[0, 1000000007]]);
+    let reduced = lll_reduce(&basis);
+    assert!(is_lll_reduced(&reduced));
+    assert!(squared_norm(&reduced[0]) < BigInt::from(1000000007i64 * 4));
+  }
+}
@@ -1,4 +1,6 @@
 pub mod primes;
 pub mod galois;
 pub mod modulo;
-pub mod bigint_utils;
\ No newline at end of file
+pub mod bigint_utils;
+pub mod gf128;
+pub mod lattice;
\ No newline at end of file
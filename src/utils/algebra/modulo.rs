@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use num_bigint::{BigUint, ToBigInt};
-use num_traits::{One, Zero};
+use num_traits::{One, ToPrimitive, Zero};
 use super::bigint_utils::extended_gcd;
 
 pub fn mod_exp(g: &BigUint, exponent: &BigUint, p: &BigUint) -> BigUint {
@@ -16,6 +18,93 @@ pub fn mod_exp(g: &BigUint, exponent: &BigUint, p: &BigUint) -> BigUint {
   res
 }
 
+/*
+  Sliding-window exponentiation. The odd powers g, g^3, ..., g^(2^window_bits - 1) are precomputed, then the exponent
+  is read from its most significant bit: zeros are single squarings, and each run of up to window_bits bits that
+  starts and ends with a 1 costs one squaring per bit and a single multiplication by the table.
+  Gives the same result as mod_exp. window_bits = 4 is a good choice for exponents of 512 to 2048 bits,
+  and it is clamped to 1..=16 so the table stays small
+*/
+pub fn mod_exp_window(g: &BigUint, exponent: &BigUint, p: &BigUint, window_bits: u64) -> BigUint {
+  let window_bits = window_bits.clamp(1, 16);
+  let g = g % p;
+  let g_squared = (&g * &g) % p;
+  let mut odd_powers = vec![g];
+  for i in 1..(1usize << (window_bits - 1)) {
+    odd_powers.push((&odd_powers[i - 1] * &g_squared) % p);
+  }
+
+  let mut res = BigUint::one();
+  let mut i = exponent.bits();
+  while i > 0 {
+    if !exponent.bit(i - 1) {
+      res = (&res * &res) % p;
+      i -= 1;
+      continue;
+    }
+    // Bits i - 1 down to start form the window, start being the lowest set bit that fits
+    let mut start = i.saturating_sub(window_bits);
+    while !exponent.bit(start) {
+      start += 1;
+    }
+    let mut window = 0usize;
+    for bit in (start..i).rev() {
+      res = (&res * &res) % p;
+      window = (window << 1) | exponent.bit(bit) as usize;
+    }
+    res = (res * &odd_powers[window >> 1]) % p;
+    i = start;
+  }
+  res
+}
+
+
+/*
+  Square root modulo an odd prime p with Tonelli-Shanks. If r is a root, p - r is the other one.
+  Euler's criterion tells the residues apart: a^((p-1)/2) = 1 (mod p) if a is a square, p - 1 if it is not.
+  For p = 3 (mod 4) the root is a^((p+1)/4). Otherwise p - 1 = q 2^s with q odd and, for a non-residue z:
+    c = z^q, t = a^q, r = a^((q+1)/2), m = s
+  and while t != 1, with i the least such that t^(2^i) = 1 and b = c^(2^(m-i-1)):
+    r = r b, c = b^2, t = t b^2, m = i
+  Returns None if a is not a square. p must be prime, or the result is meaningless
+*/
+pub fn sqrt_mod_prime(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+  let a = a % p;
+  let two = BigUint::from(2u32);
+  if a.is_zero() || p == &two {
+    return Some(a);
+  }
+  let p_minus_one = p - BigUint::one();
+  if mod_exp(&a, &(&p_minus_one >> 1), p) != BigUint::one() {
+    return None;
+  }
+  if (p % 4u32) == BigUint::from(3u32) {
+    return Some(mod_exp(&a, &((p + BigUint::one()) >> 2), p));
+  }
+
+  let s = p_minus_one.trailing_zeros().unwrap();
+  let q = &p_minus_one >> s;
+  let z = (2u32..)
+    .map(BigUint::from)
+    .find(|z| mod_exp(z, &(&p_minus_one >> 1), p) == p_minus_one)
+    .unwrap();
+  let (mut m, mut c, mut t) = (s, mod_exp(&z, &q, p), mod_exp(&a, &q, p));
+  let mut r = mod_exp(&a, &((&q + BigUint::one()) >> 1), p);
+  while !t.is_one() {
+    let mut i = 0;
+    let mut t_pow = t.clone();
+    while !t_pow.is_one() {
+      t_pow = (&t_pow * &t_pow) % p;
+      i += 1;
+    }
+    let b = mod_exp(&c, &(BigUint::one() << (m - i - 1)), p);
+    r = (r * &b) % p;
+    c = (&b * &b) % p;
+    t = (t * &c) % p;
+    m = i;
+  }
+  Some(r)
+}
 
 pub fn inv_mod(a: &BigUint, m: &BigUint) -> Option<BigUint> {
   let m_ = m.to_bigint().unwrap();
@@ -28,9 +117,58 @@ pub fn inv_mod(a: &BigUint, m: &BigUint) -> Option<BigUint> {
   }
 }
 
+// The x < m_1 * ... * m_k with x = r_i (mod m_i) for every i. The congruences are combined one at a time:
+// x = r (mod m) and x = r_i (mod m_i) give x = r + m ((r_i - r) m^-1 mod m_i) (mod m m_i).
+// None if the slices have different lengths, are empty or the moduli are not pairwise coprime
+pub fn crt(residues: &[BigUint], moduli: &[BigUint]) -> Option<BigUint> {
+  if residues.is_empty() || residues.len() != moduli.len() {
+    return None;
+  }
+  let (mut x, mut m) = (BigUint::zero(), BigUint::one());
+  for (r_i, m_i) in residues.iter().zip(moduli) {
+    let inv = inv_mod(&(&m % m_i), m_i)?;
+    let diff = (r_i % m_i + m_i - &x % m_i) % m_i;
+    x += &m * ((diff * inv) % m_i);
+    m *= m_i;
+  }
+  Some(x)
+}
+
+/*
+  Baby-step giant-step discrete logarithm: the x < order with g^x = h (mod p), order being the order of g.
+  With m = ceil(sqrt(order)) write x = i m + j, 0 <= i, j < m. The baby steps g^j go into a table, and the giant
+  steps h (g^-m)^i are looked up in it, so it takes O(sqrt(order)) time and memory.
+  None if h is not in the subgroup generated by g (or g is not invertible)
+*/
+pub fn bsgs(g: &BigUint, h: &BigUint, p: &BigUint, order: &BigUint) -> Option<BigUint> {
+  let mut m = order.sqrt();
+  if &(&m * &m) < order {
+    m += BigUint::one();
+  }
+  let steps = m.to_u64()?;
+  let mut baby_steps: HashMap<BigUint, u64> = HashMap::new();
+  let mut power = BigUint::one() % p;
+  for j in 0..steps {
+    baby_steps.entry(power.clone()).or_insert(j);
+    power = (power * g) % p;
+  }
+
+  let giant_step = inv_mod(&mod_exp(g, &m, p), p)?;
+  let mut gamma = h % p;
+  for i in 0..steps {
+    if let Some(j) = baby_steps.get(&gamma) {
+      return Some(&m * i + j);
+    }
+    gamma = (gamma * &giant_step) % p;
+  }
+  None
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  use num_bigint::RandBigInt;
+  use rand::thread_rng;
 
   #[test]
   fn test_inv_mod_simple() {
@@ -54,4 +192,69 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(result.unwrap(), BigUint::from(18633540u32));
   }
+
+  #[test]
+  fn test_mod_exp_window_matches_mod_exp() {
+    let mut rng = thread_rng();
+    for _ in 0..20 {
+      let p = rng.gen_biguint(512) | BigUint::one();
+      let (g, e) = (rng.gen_biguint(512), rng.gen_biguint(512));
+      let expected = mod_exp(&g, &e, &p);
+      for window_bits in 1..=6 {
+        assert_eq!(mod_exp_window(&g, &e, &p, window_bits), expected);
+      }
+    }
+    let (g, p) = (BigUint::from(5u32), BigUint::from(37u32));
+    assert_eq!(mod_exp_window(&g, &BigUint::zero(), &p, 4), BigUint::one());
+    assert_eq!(mod_exp_window(&g, &BigUint::from(1000u32), &p, 4), BigUint::from(7u32));
+    assert_eq!(mod_exp_window(&g, &BigUint::from(1000u32), &p, 0), BigUint::from(7u32));
+    assert_eq!(mod_exp_window(&g, &BigUint::from(1000u32), &p, 64), BigUint::from(7u32));
+  }
+
+  #[test]
+  fn test_sqrt_mod_prime() {
+    // 1000000009 = 1 (mod 8) goes through Tonelli-Shanks, 1000000007 = 3 (mod 4) through the direct formula
+    for p in [1000000009u64, 1000000007, 17, 13] {
+      let p = BigUint::from(p);
+      let a = (BigUint::from(123456789u32) * BigUint::from(123456789u32)) % &p;
+      let r = sqrt_mod_prime(&a, &p).unwrap();
+      assert_eq!((&r * &r) % &p, a);
+      let other = &p - &r;
+      assert_eq!((&other * &other) % &p, a);
+      assert_eq!(r + other, p);
+    }
+    // The squares modulo 13 are 1, 3, 4, 9, 10 and 12
+    assert_eq!(sqrt_mod_prime(&BigUint::from(5u32), &BigUint::from(13u32)), None);
+    assert_eq!(sqrt_mod_prime(&BigUint::from(5u32), &BigUint::from(1000000007u32)), None);
+    assert_eq!(sqrt_mod_prime(&BigUint::zero(), &BigUint::from(13u32)), Some(BigUint::zero()));
+  }
+
+  #[test]
+  fn test_sqrt_mod_two() {
+    let two = BigUint::from(2u32);
+    assert_eq!(sqrt_mod_prime(&BigUint::from(3u32), &two), Some(BigUint::one()));
+    assert_eq!(sqrt_mod_prime(&BigUint::from(4u32), &two), Some(BigUint::zero()));
+  }
+
+  #[test]
+  fn test_crt() {
+    let moduli = [BigUint::from(3u32), BigUint::from(5u32), BigUint::from(7u32)];
+    let residues = [BigUint::from(2u32), BigUint::from(3u32), BigUint::from(2u32)];
+    assert_eq!(crt(&residues, &moduli), Some(BigUint::from(23u32)));
+    assert_eq!(crt(&residues, &[BigUint::from(3u32), BigUint::from(6u32), BigUint::from(7u32)]), None);
+    assert_eq!(crt(&residues[..2], &moduli), None);
+  }
+
+  #[test]
+  fn test_bsgs() {
+    let (g, p, order) = (BigUint::from(5u32), BigUint::from(37u32), BigUint::from(36u32));
+    // 5^1000 = 7 (mod 37) and 5 has order 36, so the smallest exponent is 1000 mod 36 = 28
+    assert_eq!(bsgs(&g, &BigUint::from(7u32), &p, &order), Some(BigUint::from(28u32)));
+    for x in 0..36u32 {
+      let h = mod_exp(&g, &BigUint::from(x), &p);
+      assert_eq!(bsgs(&g, &h, &p, &order), Some(BigUint::from(x)));
+    }
+    // 10 generates {1, 10, 26}, which does not contain 5
+    assert_eq!(bsgs(&BigUint::from(10u32), &g, &p, &BigUint::from(3u32)), None);
+  }
 }
\ No newline at end of file
@@ -2,7 +2,9 @@ use num_bigint::{BigUint, RandBigInt};
 use num_traits::{FromBytes, One, Zero};
 use crate::utils::conversion::hex_string::HexString;
 
-use super::modulo::mod_exp;
+use num_bigint::ToBigInt;
+
+use super::{bigint_utils::extended_gcd, modulo::mod_exp};
 
 pub fn get_nist_prime() -> BigUint {
   let hex = HexString::try_from(
@@ -55,6 +57,46 @@ pub fn miller_rabin_test(n: &BigUint, k: u64) -> bool {
   true
 }
 
+const PREDICATE_ITERATIONS: u64 = 20;
+
+// miller_rabin_test assumes n is odd and n > 1
+fn is_probable_prime(n: &BigUint) -> bool {
+  if *n < BigUint::from(2u8) {
+    return false;
+  }
+  if n % 2u8 == BigUint::zero() {
+    return *n == BigUint::from(2u8);
+  }
+  miller_rabin_test(n, PREDICATE_ITERATIONS)
+}
+
+// p is prime and (p - 1) / 2 is prime
+pub fn is_safe_prime(p: &BigUint) -> bool {
+  if *p < BigUint::from(5u8) {
+    return false;
+  }
+  is_probable_prime(p) && is_probable_prime(&((p - BigUint::one()) >> 1))
+}
+
+// q is prime and 2q + 1 is prime
+pub fn is_sophie_germain(q: &BigUint) -> bool {
+  is_probable_prime(q) && is_probable_prime(&((q << 1) + BigUint::one()))
+}
+
+// Smallest prime strictly greater than n
+pub fn next_prime(n: &BigUint) -> BigUint {
+  let two = BigUint::from(2u8);
+  if *n < two {
+    return two;
+  }
+  // Only odd candidates are tried from here on
+  let mut candidate = if n % &two == BigUint::zero() { n + BigUint::one() } else { n + &two };
+  while !is_probable_prime(&candidate) {
+    candidate += &two;
+  }
+  candidate
+}
+
 pub fn generate_prime(bits: u64, iterations: u64) -> BigUint {
   let one = BigUint::one();
   let two = BigUint::from(2u8);
@@ -72,6 +114,48 @@ pub fn generate_prime(bits: u64, iterations: u64) -> BigUint {
   }
 }
 
+const POLLARD_RHO_MAX_ITERATIONS: u64 = 1 << 20;
+
+/*
+  Pollard's rho with f(x) = x^2 + 1 (mod n) and Floyd's cycle detection: x moves one step and y two, until
+  d = gcd(|x - y|, n) != 1. Modulo a factor p the sequence cycles after about sqrt(p) steps, so d is usually p.
+  If d = n the run failed and it starts again from the next x_0. Returns a nontrivial factor of n, or None
+  if n is prime (or 1) or no factor was found within POLLARD_RHO_MAX_ITERATIONS steps
+*/
+pub fn pollard_rho(n: &BigUint) -> Option<BigUint> {
+  if *n < BigUint::from(4u8) {
+    return None;
+  }
+  if (n % 2u8).is_zero() {
+    return Some(BigUint::from(2u8));
+  }
+  if is_probable_prime(n) {
+    return None;
+  }
+  let f = |x: &BigUint| (x * x + BigUint::one()) % n;
+  let gcd_with_n = |a: &BigUint| extended_gcd(&a.to_bigint().unwrap(), &n.to_bigint().unwrap()).2.to_biguint().unwrap();
+  let mut iterations = 0;
+  let mut x_0 = BigUint::from(2u8);
+  while iterations < POLLARD_RHO_MAX_ITERATIONS {
+    let (mut x, mut y) = (x_0.clone(), x_0.clone());
+    loop {
+      iterations += 1;
+      x = f(&x);
+      y = f(&f(&y));
+      let diff = if x > y { &x - &y } else { &y - &x };
+      let d = gcd_with_n(&diff);
+      if &d == n || iterations >= POLLARD_RHO_MAX_ITERATIONS {
+        break;
+      }
+      if !d.is_one() {
+        return Some(d);
+      }
+    }
+    x_0 += BigUint::one();
+  }
+  None
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -95,4 +179,49 @@ mod tests {
     let prime = generate_prime(bits, iterations);
     assert!(miller_rabin_test(&prime, iterations));
   }
-}
+
+  #[test]
+  fn test_next_prime() {
+    assert_eq!(next_prime(&BigUint::from(14u32)), BigUint::from(17u32));
+    for (n, p) in [(0u32, 2u32), (1, 2), (2, 3), (3, 5), (17, 19), (7919, 7927)] {
+      assert_eq!(next_prime(&BigUint::from(n)), BigUint::from(p));
+    }
+    let n = BigUint::one() << 256;
+    let p = next_prime(&n);
+    assert!(p > n);
+    assert!(miller_rabin_test(&p, 20));
+  }
+
+  #[test]
+  fn test_is_safe_prime() {
+    for p in [5u32, 7, 11, 23, 47, 2039] {
+      assert!(is_safe_prime(&BigUint::from(p)));
+    }
+    assert!(is_safe_prime(&get_nist_prime()));
+    for p in [2u32, 3, 13, 17, 29, 2041] {
+      assert!(!is_safe_prime(&BigUint::from(p)));
+    }
+  }
+
+  #[test]
+  fn test_is_sophie_germain() {
+    for q in [2u32, 3, 5, 11, 23, 29, 1019] {
+      assert!(is_sophie_germain(&BigUint::from(q)));
+    }
+    assert!(is_sophie_germain(&((get_nist_prime() - BigUint::one()) >> 1)));
+    for q in [0u32, 1, 7, 13, 17, 19, 21] {
+      assert!(!is_sophie_germain(&BigUint::from(q)));
+    }
+  }
+
+  #[test]
+  fn test_pollard_rho() {
+    let (p, q) = (generate_prime(32, 10), generate_prime(32, 10));
+    let n = &p * &q;
+    let factor = pollard_rho(&n).unwrap();
+    assert!((&n % &factor).is_zero());
+    assert!(factor == p || factor == q);
+    assert_eq!(pollard_rho(&BigUint::from(8051u32)).map(|d| BigUint::from(8051u32) % d), Some(BigUint::zero()));
+    assert_eq!(pollard_rho(&BigUint::from(1000000007u32)), None);
+  }
+}
\ No newline at end of file
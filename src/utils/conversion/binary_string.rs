@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 use super::{conversion::{bytes_vector_to_base64, xor_bytes_vectors, ConversionError}, hex_string::HexString};
 
@@ -7,9 +7,28 @@ pub struct BinaryString {
   string: String,
 }
 
+// "{}" prints the raw bits, "{:#}" groups them in bytes (e.g. "01000001 01110100")
 impl fmt::Display for BinaryString {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{}", self.string)
+    if f.alternate() {
+      let bytes: Vec<&str> = self
+        .string
+        .as_bytes()
+        .chunks(8)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap())
+        .collect();
+      write!(f, "{}", bytes.join(" "))
+    } else {
+      write!(f, "{}", self.string)
+    }
+  }
+}
+
+// Accepts both the raw and the grouped format, whitespace is ignored
+impl FromStr for BinaryString {
+  type Err = ConversionError;
+  fn from_str(str: &str) -> Result<Self, ConversionError> {
+    Self::try_from(str.chars().filter(|c| !c.is_whitespace()).collect::<String>())
   }
 }
 
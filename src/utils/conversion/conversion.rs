@@ -1,5 +1,10 @@
 use std::{fmt, string::FromUtf8Error};
 
+use num_bigint::BigUint;
+
+use crate::utils::aes::{aes_error::AESError, constants::AES_BLOCK_SIZE, utils::pkcs_padding};
+use super::hex_string::HexString;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConversionError {
   InvalidBinaryCharError(char),
@@ -7,7 +12,11 @@ pub enum ConversionError {
   InvalidSizeOfString(usize),
   InvalidBase64Character(u8),
   InvalidBase64InputLength,
+  NonZeroBase64TrailingBits,
   SizesDoNotMatch(usize, usize),
+  InputTooLong(usize, usize),
+  WrongNumberOfFields(usize, usize),
+  InvalidDecimalByte(String),
   Utf8Error(FromUtf8Error)
 }
 
@@ -26,6 +35,15 @@ impl fmt::Display for ConversionError {
       Self::SizesDoNotMatch(a, b) => {
         write!(f, "Sizes of strings must be equal, they are {a} and {b}")
       }
+      Self::InputTooLong(len, target_len) => {
+        write!(f, "Input of length {len} does not fit in {target_len} bytes")
+      }
+      Self::WrongNumberOfFields(found, expected) => {
+        write!(f, "Found {found} comma separated fields, expected {expected}")
+      }
+      Self::InvalidDecimalByte(token) => {
+        write!(f, "{token} is not a decimal byte between 0 and 255")
+      }
       Self::Utf8Error(error) => {
         write!(f, "UTF8 conversion error {error}.")
       }
@@ -35,6 +53,9 @@ impl fmt::Display for ConversionError {
       Self::InvalidBase64InputLength => {
         write!(f, "A string in Base64 cannot have this length.")
       }
+      Self::NonZeroBase64TrailingBits => {
+        write!(f, "The bits after the last full byte of a Base64 string must be zero.")
+      }
     }
   }
 }
@@ -45,6 +66,39 @@ impl From<FromUtf8Error> for ConversionError {
   }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncodingGuess {
+  Binary,
+  Hex,
+  Base64,
+  Raw,
+}
+
+// Classifies an opaque input by its alphabet and length, the narrowest alphabet wins.
+// Whitespace is ignored, as it is when parsing any of these encodings
+pub fn guess_encoding(input: &str) -> EncodingGuess {
+  let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+  if compact.is_empty() {
+    return EncodingGuess::Raw;
+  }
+  if compact.len().is_multiple_of(8) && compact.chars().all(|c| c == '0' || c == '1') {
+    return EncodingGuess::Binary;
+  }
+  let hex_digits = compact.strip_prefix("0x").unwrap_or(&compact);
+  if !hex_digits.is_empty() && hex_digits.len().is_multiple_of(2) && hex_digits.chars().all(|c| c.is_ascii_hexdigit()) {
+    return EncodingGuess::Hex;
+  }
+  let data = compact.trim_end_matches('=');
+  let valid_base64 = compact.len().is_multiple_of(4)
+    && compact.len() - data.len() <= 2
+    && data.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+    && base64_to_bytes_vector_strict(&compact).is_ok();
+  if valid_base64 {
+    return EncodingGuess::Base64;
+  }
+  EncodingGuess::Raw
+}
+
 pub fn hex_char_to_binary(c: char) -> Result  <String, ConversionError> {
   match c {
     '0' => Ok(String::from("0000")),
@@ -68,9 +122,13 @@ pub fn hex_char_to_binary(c: char) -> Result  <String, ConversionError> {
   }
 }
 
+pub const BASE64_STANDARD_ALPHABET: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// RFC 4648, section 5: '-' and '_' instead of '+' and '/', so it can go in URLs and file names
+pub const BASE64_URL_SAFE_ALPHABET: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
 pub fn bytes_vector_to_base64(bytes: Vec<u8>) -> Result<String, ConversionError> {
-  const BASE64_TABLE: &[u8; 64] =
-    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
   let mut result = String::from("");
   for chunk in bytes.chunks(3) {
     let (b0, b1, b2) = (
@@ -80,10 +138,10 @@ pub fn bytes_vector_to_base64(bytes: Vec<u8>) -> Result<String, ConversionError>
     );
     let combined: u32 = ((b0 << 16) | (b1 << 8) | b2) as u32;
 
-    result.push(BASE64_TABLE[((combined >> 18) & 0x3f) as usize] as char); // bits 0..5
-    result.push(BASE64_TABLE[((combined >> 12) & 0x3f) as usize] as char); // bits 6..11
-    result.push(BASE64_TABLE[((combined >> 6) & 0x3f) as usize] as char); // bits 12..17
-    result.push(BASE64_TABLE[(combined & 0x3f) as usize] as char); // bits 18..23
+    result.push(BASE64_STANDARD_ALPHABET[((combined >> 18) & 0x3f) as usize] as char); // bits 0..5
+    result.push(BASE64_STANDARD_ALPHABET[((combined >> 12) & 0x3f) as usize] as char); // bits 6..11
+    result.push(BASE64_STANDARD_ALPHABET[((combined >> 6) & 0x3f) as usize] as char); // bits 12..17
+    result.push(BASE64_STANDARD_ALPHABET[(combined & 0x3f) as usize] as char); // bits 18..23
   }
   let length: usize = bytes.len();
   if length % 3 == 1 {
@@ -101,20 +159,60 @@ pub fn bytes_vector_to_base64(bytes: Vec<u8>) -> Result<String, ConversionError>
 }
 
 pub fn base64_to_bytes_vector<S: AsRef<str>>(base64_str: S) -> Result<Vec<u8>, ConversionError> {
-  const BASE64_TABLE: &[u8; 64] =
-    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-  let mut bytes = Vec::new();
+  base64_decode_with_alphabet(base64_str, BASE64_STANDARD_ALPHABET, Some('='))
+}
+
+// Decodes Base64 written with another alphabet, e.g. the ones of bcrypt or crypt(3).
+// With pad = None the input has no padding and any character outside the alphabet is an error
+pub fn base64_decode_with_alphabet<S: AsRef<str>>(
+  input: S,
+  alphabet: &[u8; 64],
+  pad: Option<char>,
+) -> Result<Vec<u8>, ConversionError> {
+  decode_base64(input.as_ref(), alphabet, pad, false)
+}
+
+// Like base64_to_bytes_vector, but the bits left over after the last full byte must be zero,
+// as they are in any canonical encoding. Otherwise the input was corrupted
+pub fn base64_to_bytes_vector_strict<S: AsRef<str>>(base64_str: S) -> Result<Vec<u8>, ConversionError> {
+  decode_base64(base64_str.as_ref(), BASE64_STANDARD_ALPHABET, Some('='), true)
+}
+
+// URL-safe Base64 without padding, as used by JWT. Leftover bits must be zero like in the strict decoder
+pub fn base64_url_to_bytes_vector<S: AsRef<str>>(base64_str: S) -> Result<Vec<u8>, ConversionError> {
+  decode_base64(base64_str.as_ref(), BASE64_URL_SAFE_ALPHABET, None, true)
+}
+
+// Decodes every '.'-separated segment of a JWT-like token (header, payload and signature)
+pub fn decode_jwt_segments(token: &str) -> Result<Vec<Vec<u8>>, ConversionError> {
+  token.split('.').map(base64_url_to_bytes_vector).collect()
+}
+
+// Exact number of bytes a Base64 input decodes to. Every character before the '=' padding carries 6 bits,
+// and whitespace is skipped as in the decoder
+pub fn base64_decoded_len(input: &str) -> usize {
+  let data = input.split('=').next().unwrap_or_default();
+  data.bytes().filter(|byte| !byte.is_ascii_whitespace()).count() * 3 / 4
+}
+
+fn decode_base64(
+  base64_str: &str,
+  alphabet: &[u8; 64],
+  pad: Option<char>,
+  strict: bool,
+) -> Result<Vec<u8>, ConversionError> {
+  let mut bytes = Vec::with_capacity(base64_decoded_len(base64_str));
   let mut buffer = 0u32;
   let mut bits_collected = 0;
 
-  for byte in base64_str.as_ref().bytes() {
-    if byte == b'=' {
+  for byte in base64_str.bytes() {
+    if pad == Some(byte as char) {
       break;
     } else if byte.is_ascii_whitespace() {
       continue;
     }
 
-    let value = match BASE64_TABLE.iter().position(|&c| c == byte) {
+    let value = match alphabet.iter().position(|&c| c == byte) {
       Some(v) => v as u32,
       None => return Err(ConversionError::InvalidBase64Character(byte)),
     };
@@ -127,9 +225,16 @@ pub fn base64_to_bytes_vector<S: AsRef<str>>(base64_str: S) -> Result<Vec<u8>, C
       bytes.push((buffer >> bits_collected) as u8);
     }
   }
-  //if bits_collected > 0 {
-  //  return Err(ConversionError::InvalidBase64InputLength);
-  //}
+
+  if strict {
+    // A single dangling character (6 bits) can never be produced by an encoder
+    if bits_collected >= 6 {
+      return Err(ConversionError::InvalidBase64InputLength);
+    }
+    if buffer & ((1 << bits_collected) - 1) != 0 {
+      return Err(ConversionError::NonZeroBase64TrailingBits);
+    }
+  }
 
   Ok(bytes)
 }
@@ -162,12 +267,131 @@ pub fn repeating_key_xor<S: AsRef<[u8]>, T: AsRef<[u8]>>(text: S, key: T) -> Vec
     .collect()
 }
 
+pub fn into_fixed_blocks<S: AsRef<[u8]>>(bytes: S) -> Result<Vec<[u8; AES_BLOCK_SIZE]>, AESError> {
+  let bytes = bytes.as_ref();
+  if bytes.len() % AES_BLOCK_SIZE != 0 {
+    return Err(AESError::InvalidBlockSize(bytes.len()));
+  }
+  Ok(
+    bytes
+      .chunks_exact(AES_BLOCK_SIZE)
+      .map(|chunk| chunk.try_into().unwrap())
+      .collect(),
+  )
+}
+
+// Applies PKCS#7 padding first, so any input length is accepted
+pub fn into_fixed_blocks_padded<S: AsRef<[u8]>>(bytes: S) -> Vec<[u8; AES_BLOCK_SIZE]> {
+  into_fixed_blocks(pkcs_padding(&bytes, AES_BLOCK_SIZE as u8)).unwrap()
+}
+
+// Offset of the first occurrence of needle inside haystack
+pub fn find_subsequence<S: AsRef<[u8]>, T: AsRef<[u8]>>(haystack: S, needle: T) -> Option<usize> {
+  let (haystack, needle) = (haystack.as_ref(), needle.as_ref());
+  if needle.is_empty() {
+    return Some(0);
+  }
+  haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// Fraction of bytes in the printable ASCII range 0x20..=0x7E. Useful to score decryption attempts
+pub fn printable_ratio<S: AsRef<[u8]>>(bytes: S) -> f64 {
+  let bytes = bytes.as_ref();
+  if bytes.is_empty() {
+    return 0.0;
+  }
+  let printable = bytes.iter().filter(|&&b| (0x20..=0x7e).contains(&b)).count();
+  printable as f64 / bytes.len() as f64
+}
+
+// Prepends zeros until the length is len (as a big-endian number, it keeps its value)
+pub fn left_pad_to<S: AsRef<[u8]>>(bytes: S, len: usize) -> Result<Vec<u8>, ConversionError> {
+  let bytes = bytes.as_ref();
+  if bytes.len() > len {
+    return Err(ConversionError::InputTooLong(bytes.len(), len));
+  }
+  Ok([vec![0x00; len - bytes.len()], bytes.to_vec()].concat())
+}
+
+pub fn right_pad_to<S: AsRef<[u8]>>(bytes: S, len: usize) -> Result<Vec<u8>, ConversionError> {
+  let bytes = bytes.as_ref();
+  if bytes.len() > len {
+    return Err(ConversionError::InputTooLong(bytes.len(), len));
+  }
+  let mut result = bytes.to_vec();
+  result.resize(len, 0x00);
+  Ok(result)
+}
+
+// Splits the first A + B bytes of a digest into two keys, e.g. the encryption and MAC keys of a KDF.
+// Bytes after them are ignored. None if the digest is shorter than A + B
+pub fn split_digest<const A: usize, const B: usize>(digest: &[u8]) -> Option<([u8; A], [u8; B])> {
+  let (first, rest) = digest.split_at_checked(A)?;
+  Some((first.try_into().ok()?, rest.get(..B)?.try_into().ok()?))
+}
+
+// Hex of x left-padded with zeros to byte_len bytes, so values modulo the same n print with the same width.
+// Values that do not fit are printed whole
+pub fn biguint_to_hex_fixed(x: &BigUint, byte_len: usize) -> HexString {
+  let bytes = x.to_bytes_be();
+  let padded = left_pad_to(&bytes, byte_len).unwrap_or(bytes);
+  HexString::try_from(padded).unwrap()
+}
+
+// Keystream bytes at [offset, offset + |known_plaintext|) of any XOR-based cipher
+pub fn recover_keystream_segment<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+  ciphertext: S,
+  known_plaintext: T,
+  offset: usize,
+) -> Result<Vec<u8>, ConversionError> {
+  let (ciphertext, known_plaintext) = (ciphertext.as_ref(), known_plaintext.as_ref());
+  let end = offset + known_plaintext.len();
+  if end > ciphertext.len() {
+    return Err(ConversionError::SizesDoNotMatch(ciphertext.len(), end));
+  }
+  xor_bytes_vectors(&ciphertext[offset..end], known_plaintext)
+}
+
+// XORs the keystream into the text starting at offset. Bytes not covered by the keystream are left as they are
+pub fn apply_keystream<S: AsRef<[u8]>, T: AsRef<[u8]>>(text: S, keystream: T, offset: usize) -> Vec<u8> {
+  let mut result = text.as_ref().to_vec();
+  for (byte, key_byte) in result.iter_mut().skip(offset).zip(keystream.as_ref()) {
+    *byte ^= key_byte;
+  }
+  result
+}
+
+// [72, 79, 76, 65] -> "72 79 76 65", as tools that dump bytes in decimal print them
+pub fn bytes_to_decimal_string<S: AsRef<[u8]>>(bytes: S) -> String {
+  bytes.as_ref().iter().map(|byte| byte.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+// Inverse of bytes_to_decimal_string. Any whitespace separates the bytes
+pub fn decimal_string_to_bytes(decimal_str: &str) -> Result<Vec<u8>, ConversionError> {
+  decimal_str
+    .split_whitespace()
+    .map(|token| token.parse::<u8>().map_err(|_| ConversionError::InvalidDecimalByte(token.to_string())))
+    .collect()
+}
 
 #[cfg(test)]
 mod tests {
   use assert_matches::assert_matches;
+  use rand::{thread_rng, Rng};
+  use num_bigint::BigUint;
 
-use crate::utils::conversion::{binary_string::BinaryString, conversion::{bytes_vector_to_base64, hex_char_to_binary, ConversionError}, hex_string::HexString};
+use crate::utils::{aes::{aes::AES, aes_error::AESError, utils::AESMode}, mac::sha1::Sha1, conversion::{binary_string::BinaryString, conversion::{apply_keystream, biguint_to_hex_fixed, guess_encoding, EncodingGuess, base64_decode_with_alphabet, base64_decoded_len, base64_to_bytes_vector, BASE64_STANDARD_ALPHABET, base64_to_bytes_vector_strict, bytes_vector_to_base64, find_subsequence, hex_char_to_binary, into_fixed_blocks, into_fixed_blocks_padded, left_pad_to, printable_ratio, recover_keystream_segment, right_pad_to, repeating_key_xor, split_digest, base64_url_to_bytes_vector, decode_jwt_segments, bytes_to_decimal_string, decimal_string_to_bytes, ConversionError}, hex_string::HexString}};
+
+  #[test]
+  fn decimal_string_round_trip() {
+    let bytes = Vec::from([72, 79, 76, 65, 0, 255]);
+    assert_eq!(bytes_to_decimal_string(&bytes), "72 79 76 65 0 255");
+    assert_eq!(decimal_string_to_bytes(&bytes_to_decimal_string(&bytes)), Ok(bytes));
+    assert_eq!(decimal_string_to_bytes(" 1\n 2\t3 "), Ok(vec![1, 2, 3]));
+    assert_eq!(bytes_to_decimal_string([]), "");
+    assert_eq!(decimal_string_to_bytes(""), Ok(vec![]));
+    assert_eq!(decimal_string_to_bytes("72 256"), Err(ConversionError::InvalidDecimalByte(String::from("256"))));
+  }
 
   #[test]
   fn hex_to_binary_valid_char() {
@@ -215,6 +439,20 @@ use crate::utils::conversion::{binary_string::BinaryString, conversion::{bytes_v
     );
   }
 
+  #[test]
+  fn grouped_binary_string_round_trip() {
+    let binary = BinaryString::try_from(b"At".to_vec()).unwrap();
+    assert_eq!(format!("{}", binary), "0100000101110100");
+    assert_eq!(format!("{:#}", binary), "01000001 01110100");
+    assert_eq!(format!("{:#}", binary).parse::<BinaryString>().unwrap(), binary);
+    assert_eq!(" 01000001\n01110100 ".parse::<BinaryString>().unwrap(), binary);
+    assert_eq!("0100000 1".parse::<BinaryString>().unwrap().as_text().unwrap(), "A");
+    assert_eq!(
+      "01000001 0111".parse::<BinaryString>().unwrap_err(),
+      ConversionError::InvalidSizeOfString(8)
+    );
+  }
+
   #[test]
   fn valid_binary_string_to_vector_u8() {
     assert_eq!(
@@ -250,6 +488,70 @@ use crate::utils::conversion::{binary_string::BinaryString, conversion::{bytes_v
     );
   }
 
+  #[test]
+  fn strict_base64_rejects_nonzero_trailing_bits() {
+    for (base64, expected) in [("SE9MQQ==", "HOLA"), ("SE9MQVE=", "HOLAQ"), ("SE9MQVFV", "HOLAQU")] {
+      assert_eq!(base64_to_bytes_vector_strict(base64).unwrap(), expected.as_bytes());
+    }
+    // 'R' and 'F' carry the same first bits as 'Q' and 'E', but leave nonzero bits behind
+    assert_eq!(base64_to_bytes_vector("SE9MQR==").unwrap(), b"HOLA");
+    assert_eq!(base64_to_bytes_vector_strict("SE9MQR=="), Err(ConversionError::NonZeroBase64TrailingBits));
+    assert_eq!(base64_to_bytes_vector_strict("SE9MQVF="), Err(ConversionError::NonZeroBase64TrailingBits));
+    assert_eq!(base64_to_bytes_vector_strict("SE9MQ"), Err(ConversionError::InvalidBase64InputLength));
+  }
+
+  #[test]
+  fn base64_decoded_len_matches_decoder() {
+    for base64 in ["SE9MQVFV", "SE9MQVE=", "SE9MQQ==", "SE9M\nQQ=="] {
+      assert_eq!(base64_decoded_len(base64), base64_to_bytes_vector(base64).unwrap().len());
+    }
+    assert_eq!(base64_decoded_len(""), 0);
+  }
+
+  #[test]
+  fn base64_decode_with_shuffled_alphabet() {
+    let bytes = b"I'm killing your brain like a poisonous mushroom!".to_vec();
+    let standard = bytes_vector_to_base64(bytes.clone()).unwrap();
+    // Same encoding under another alphabet, character by character
+    let translate = |alphabet: &[u8; 64], pad: &str| -> String {
+      standard
+        .bytes()
+        .map(|c| match BASE64_STANDARD_ALPHABET.iter().position(|&x| x == c) {
+          Some(i) => (alphabet[i] as char).to_string(),
+          None => pad.to_string(),
+        })
+        .collect()
+    };
+
+    let mut shuffled = *BASE64_STANDARD_ALPHABET;
+    shuffled.reverse();
+    shuffled.swap(3, 40);
+    let encoded = translate(&shuffled, "=");
+    assert_ne!(encoded, standard);
+    assert_eq!(base64_decode_with_alphabet(&encoded, &shuffled, Some('=')).unwrap(), bytes);
+    assert_eq!(base64_decode_with_alphabet(&standard, BASE64_STANDARD_ALPHABET, Some('=')).unwrap(), bytes);
+
+    let bcrypt = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let encoded = translate(bcrypt, "");
+    assert_eq!(base64_decode_with_alphabet(&encoded, bcrypt, None).unwrap(), bytes);
+    assert_matches!(
+      base64_decode_with_alphabet(encoded + "=", bcrypt, None),
+      Err(ConversionError::InvalidBase64Character(b'='))
+    );
+  }
+
+  #[test]
+  fn guess_encoding_of_inputs() {
+    assert_eq!(guess_encoding("49276d206b696c6c696e6720796f7572"), EncodingGuess::Hex);
+    assert_eq!(guess_encoding("0x1C0111001F010100"), EncodingGuess::Hex);
+    assert_eq!(guess_encoding("SSdtIGtpbGxpbmcgeW91cg=="), EncodingGuess::Base64);
+    assert_eq!(guess_encoding("SE9MQVFV"), EncodingGuess::Base64);
+    assert_eq!(guess_encoding("01000001 01110100"), EncodingGuess::Binary);
+    assert_eq!(guess_encoding("HOLA QUE TAL?"), EncodingGuess::Raw);
+    assert_eq!(guess_encoding("SE9MQR=="), EncodingGuess::Raw); // Nonzero trailing bits
+    assert_eq!(guess_encoding(""), EncodingGuess::Raw);
+  }
+
   #[test]
   fn from_hex_to_base64_test() {
     assert_eq!(
@@ -281,4 +583,206 @@ use crate::utils::conversion::{binary_string::BinaryString, conversion::{bytes_v
         .unwrap()
     )
   }
+
+  #[test]
+  fn recover_keystream_from_known_header() {
+    let key = b"YELLOW SUBMARINE";
+    let known = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n";
+    let message1 = [known.as_ref(), b"Hola que tal, aguante Boca"].concat();
+    let message2 = b"SOY BOSTERO DE LA CUNA A LA TUMBA Y NUNCA DESCENDERE".to_vec();
+    let ciphertext1 = AES::encode(&message1, key, AESMode::CTR(0)).unwrap();
+    let ciphertext2 = AES::encode(&message2, key, AESMode::CTR(0)).unwrap();
+
+    let keystream = recover_keystream_segment(&ciphertext1, known, 0).unwrap();
+    assert_eq!(apply_keystream(&ciphertext1, &keystream, 0)[..known.len()], known[..]);
+    assert_eq!(apply_keystream(&ciphertext2, &keystream, 0)[..known.len()], message2[..known.len()]);
+
+    let segment = recover_keystream_segment(&ciphertext1, &message1[10..20], 10).unwrap();
+    assert_eq!(segment, keystream[10..20]);
+    assert_eq!(apply_keystream(&ciphertext2, &segment, 10)[10..20], message2[10..20]);
+  }
+
+  #[test]
+  fn recover_keystream_out_of_bounds() {
+    assert_matches!(
+      recover_keystream_segment([0u8; 8], [0u8; 4], 6).unwrap_err(),
+      ConversionError::SizesDoNotMatch(8, 10)
+    );
+  }
+
+  #[test]
+  fn pad_to_length() {
+    assert_eq!(left_pad_to([1, 2, 3], 5).unwrap(), vec![0, 0, 1, 2, 3]);
+    assert_eq!(right_pad_to([1, 2, 3], 5).unwrap(), vec![1, 2, 3, 0, 0]);
+  }
+
+  #[test]
+  fn pad_to_same_length() {
+    assert_eq!(left_pad_to([1, 2, 3], 3).unwrap(), vec![1, 2, 3]);
+    assert_eq!(right_pad_to([1, 2, 3], 3).unwrap(), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn pad_to_shorter_length() {
+    assert_matches!(
+      left_pad_to([1, 2, 3], 2).unwrap_err(),
+      ConversionError::InputTooLong(3, 2)
+    );
+    assert_matches!(
+      right_pad_to([1, 2, 3], 2).unwrap_err(),
+      ConversionError::InputTooLong(3, 2)
+    );
+  }
+
+  #[test]
+  fn decode_jwt_into_segments() {
+    // The example of RFC 7519, section 3.1
+    let token = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.\
+      eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ.\
+      dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    let segments = decode_jwt_segments(token).unwrap();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[0], b"{\"typ\":\"JWT\",\r\n \"alg\":\"HS256\"}");
+    assert_eq!(
+      segments[1],
+      b"{\"iss\":\"joe\",\r\n \"exp\":1300819380,\r\n \"http://example.com/is_root\":true}"
+    );
+    assert_eq!(segments[2].len(), 32);
+    assert_eq!(segments[2][..3], [0x74, 0x18, 0xdf]);
+    assert_eq!(base64_url_to_bytes_vector("-_8").unwrap(), vec![0xfb, 0xff]);
+    assert_matches!(decode_jwt_segments("eyJ0.a+b"), Err(ConversionError::InvalidBase64Character(b'+')));
+  }
+
+  #[test]
+  fn split_digest_into_keys() {
+    let digest: Vec<u8> = (0..32).collect();
+    let (first, second) = split_digest::<16, 16>(&digest).unwrap();
+    assert_eq!((first.as_slice(), second.as_slice()), (&digest[..16], &digest[16..]));
+    let sha1_digest = Sha1::hash(b"BOCA YO TE AMO");
+    assert_eq!(split_digest::<16, 16>(&sha1_digest), None);
+    assert_eq!(split_digest::<16, 4>(&sha1_digest).unwrap().1, sha1_digest[16..]);
+  }
+
+  #[test]
+  fn hex_string_from_array_matches_vec_path() {
+    let digest = Sha1::hash(b"BOCA YO TE AMO");
+    assert_eq!(HexString::from_array(&digest), HexString::try_from(digest.to_vec()).unwrap());
+    assert_eq!(HexString::from_array(&[0x00, 0x0f, 0xa0]).as_ref(), "000fa0");
+    assert_eq!(HexString::from_array(&[]).as_ref(), "");
+  }
+
+  #[test]
+  fn random_hex_string() {
+    for num_bytes in [0, 1, 16, 100] {
+      let hex = HexString::random(num_bytes);
+      assert_eq!(hex.as_ref().len(), 2 * num_bytes);
+      assert_eq!(hex.as_vector_of_bytes().unwrap().len(), num_bytes);
+    }
+    assert_ne!(HexString::random(16), HexString::random(16));
+  }
+
+  #[test]
+  fn biguint_as_fixed_width_hex() {
+    assert_eq!(biguint_to_hex_fixed(&BigUint::from(0xabcu32), 4).as_ref(), "00000abc");
+    assert_eq!(biguint_to_hex_fixed(&BigUint::from(0u32), 2).as_ref(), "0000");
+    assert_eq!(biguint_to_hex_fixed(&BigUint::from(0x123456u32), 2).as_ref(), "123456");
+  }
+
+  #[test]
+  fn hex_string_xor_with_cycling() {
+    let text = b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+    let (hex, key) = (HexString::try_from(text.to_vec()).unwrap(), HexString::try_from("494345").unwrap());
+    let expected = HexString::try_from(repeating_key_xor(text, b"ICE")).unwrap();
+    assert_eq!(hex.xor_with_cycling(&key).unwrap(), expected);
+    assert_eq!(key.xor_with_cycling(&hex).unwrap(), expected);
+    assert_eq!(hex.xor_with_cycling(&hex).unwrap(), HexString::try_from(vec![0; text.len()]).unwrap());
+    assert_eq!(hex.xor_with_cycling(&HexString::try_from("").unwrap()).unwrap(), hex);
+  }
+
+  #[test]
+  fn hex_string_from_ascii() {
+    let hex = HexString::from_ascii("Hello");
+    assert_eq!(hex, HexString::try_from("48656c6c6f").unwrap());
+    assert_eq!(hex.as_text().unwrap(), "Hello");
+    assert!(HexString::from_ascii("").is_empty());
+  }
+
+  #[test]
+  fn hex_string_as_integer() {
+    let hex = HexString::try_from("0102030405060708").unwrap();
+    assert_eq!(hex.as_biguint_be().unwrap(), BigUint::from(0x0102030405060708u64));
+    assert_eq!(hex.as_biguint_le().unwrap(), BigUint::from(0x0807060504030201u64));
+    assert_eq!(hex.as_u64_be().unwrap(), 0x0102030405060708);
+    assert_eq!(HexString::try_from("abc").unwrap().as_u64_be().unwrap(), 0xabc);
+    assert_eq!(HexString::try_from("").unwrap().as_biguint_be().unwrap(), BigUint::from(0u8));
+    assert_matches!(
+      HexString::try_from("010203040506070809").unwrap().as_u64_be(),
+      Err(ConversionError::InputTooLong(9, 8))
+    );
+  }
+
+  #[test]
+  fn hex_string_length_in_bytes() {
+    let hex = HexString::try_from("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap();
+    assert_eq!(hex.len_bytes(), 20);
+    assert!(!hex.is_empty());
+    assert_eq!(HexString::try_from("abc").unwrap().len_bytes(), 2);
+    let empty = HexString::try_from("").unwrap();
+    assert_eq!(empty.len_bytes(), 0);
+    assert!(empty.is_empty());
+  }
+
+  #[test]
+  fn xor_hex_with_repeating_key() {
+    let plaintext = HexString::try_from(
+      b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal".to_vec(),
+    )
+    .unwrap();
+    let ciphertext = HexString::try_from(
+      "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"
+    ).unwrap();
+    assert_eq!(plaintext.xor_repeating(b"ICE").unwrap(), ciphertext);
+    assert_eq!(ciphertext.xor_repeating(b"ICE").unwrap(), plaintext);
+  }
+
+  #[test]
+  fn printable_ratio_of_text_and_random_bytes() {
+    assert_eq!(printable_ratio(b"Hola que tal, aguante Boca!"), 1.0);
+    assert_eq!(printable_ratio(b"ab\x00\xff"), 0.5);
+    assert_eq!(printable_ratio(b""), 0.0);
+
+    // 95 of the 256 byte values are printable
+    let random_bytes: Vec<u8> = (0..100000).map(|_| thread_rng().gen()).collect();
+    assert!((printable_ratio(&random_bytes) - 95.0 / 256.0).abs() < 0.02);
+  }
+
+  #[test]
+  fn find_subsequence_offset() {
+    let haystack = b"comment1=cooking%20MCs;userdata=;admin=true;;comment2=%20like";
+    assert_eq!(find_subsequence(haystack, b";admin=true;"), Some(32));
+    assert_eq!(find_subsequence(haystack, b"comment"), Some(0));
+    assert_eq!(find_subsequence(haystack, b""), Some(0));
+    assert_eq!(find_subsequence(haystack, b";admin=false;"), None);
+    assert_eq!(find_subsequence(b"abc", b"abcd"), None);
+  }
+
+  #[test]
+  fn into_fixed_blocks_aligned() {
+    let bytes: Vec<u8> = (0..32).collect();
+    let blocks = into_fixed_blocks(&bytes).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].to_vec(), bytes[..16]);
+    assert_eq!(blocks[1].to_vec(), bytes[16..]);
+    assert!(into_fixed_blocks([]).unwrap().is_empty());
+  }
+
+  #[test]
+  fn into_fixed_blocks_misaligned() {
+    assert_eq!(into_fixed_blocks([0u8; 20]), Err(AESError::InvalidBlockSize(20)));
+    let blocks = into_fixed_blocks_padded(b"YELLOW SUBMARINE!!");
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(&blocks[0], b"YELLOW SUBMARINE");
+    assert_eq!(&blocks[1][..2], b"!!");
+    assert_eq!(blocks[1][2..], [14u8; 14]);
+  }
 }
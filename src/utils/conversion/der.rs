@@ -0,0 +1,85 @@
+// The subset of ASN.1 DER used by the signatures: INTEGER and SEQUENCE with definite lengths
+use num_bigint::BigUint;
+
+pub const DER_SEQUENCE_TAG: u8 = 0x30;
+pub const DER_INTEGER_TAG: u8 = 0x02;
+
+pub fn der_encode_length(len: usize) -> Vec<u8> {
+  if len < 0x80 {
+    return vec![len as u8];
+  }
+  let len_bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|&b| b == 0).collect();
+  [vec![0x80 | len_bytes.len() as u8], len_bytes].concat()
+}
+
+pub fn der_encode_integer(n: &BigUint) -> Vec<u8> {
+  let mut bytes = n.to_bytes_be();
+  // The integers are signed, so a leading 1 bit needs an extra zero byte
+  if bytes[0] & 0x80 != 0 {
+    bytes.insert(0, 0x00);
+  }
+  [vec![DER_INTEGER_TAG], der_encode_length(bytes.len()), bytes].concat()
+}
+
+pub fn der_encode_sequence(elements: &[Vec<u8>]) -> Vec<u8> {
+  let contents = elements.concat();
+  [vec![DER_SEQUENCE_TAG], der_encode_length(contents.len()), contents].concat()
+}
+
+// Reads a tag and its length, returns (contents, rest). None if the length does not fit in the input
+pub fn der_decode_element(bytes: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+  if bytes.len() < 2 || bytes[0] != tag {
+    return None;
+  }
+  let (len, header_len) = if bytes[1] < 0x80 {
+    (bytes[1] as usize, 2)
+  } else {
+    let len_size = (bytes[1] & 0x7f) as usize;
+    if len_size == 0 || len_size > std::mem::size_of::<usize>() || bytes.len() < 2 + len_size {
+      return None;
+    }
+    let len = bytes[2..2 + len_size].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+    // DER requires the shortest length encoding
+    if len < 0x80 || bytes[2] == 0 {
+      return None;
+    }
+    (len, 2 + len_size)
+  };
+  let end = header_len.checked_add(len)?;
+  if bytes.len() < end {
+    return None;
+  }
+  Some((&bytes[header_len..end], &bytes[end..]))
+}
+
+// Only non-negative integers in their minimal encoding are accepted
+pub fn der_decode_integer(bytes: &[u8]) -> Option<(BigUint, &[u8])> {
+  let (contents, rest) = der_decode_element(bytes, DER_INTEGER_TAG)?;
+  match contents {
+    [] => None,
+    [first, ..] if first & 0x80 != 0 => None, // negative
+    [0x00, second, ..] if second & 0x80 == 0 => None, // non-minimal
+    _ => Some((BigUint::from_bytes_be(contents), rest)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_der_length_encoding() {
+    assert_eq!(der_encode_length(0x7f), vec![0x7f]);
+    assert_eq!(der_encode_length(0x80), vec![0x81, 0x80]);
+    assert_eq!(der_encode_length(0x1234), vec![0x82, 0x12, 0x34]);
+    let element = [vec![DER_SEQUENCE_TAG], der_encode_length(0x80), vec![0xaa; 0x80]].concat();
+    assert_eq!(der_decode_element(&element, DER_SEQUENCE_TAG), Some((&element[3..], &[][..])));
+  }
+
+  #[test]
+  fn test_der_decode_huge_length() {
+    // The length would overflow header_len + len
+    let bytes = [0x30, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(der_decode_element(&bytes, DER_SEQUENCE_TAG), None);
+  }
+}
@@ -1,8 +1,11 @@
 use std::collections::HashSet;
 use std::fmt;
 
+use num_bigint::BigUint;
+use rand::{thread_rng, Rng};
+
 use super::binary_string::BinaryString;
-use super::conversion::{hex_char_to_binary, xor_bytes_vectors, ConversionError};
+use super::conversion::{hex_char_to_binary, repeating_key_xor, xor_bytes_vectors, ConversionError};
 
 #[derive(Debug)]
 pub struct HexString {
@@ -47,6 +50,38 @@ impl TryFrom<Vec<u8>> for HexString {
   }
 }
 
+impl HexString {
+  // Same as HexString::try_from(arr.to_vec()) for digests and blocks, without the intermediate Vec
+  pub fn from_array<const N: usize>(arr: &[u8; N]) -> Self {
+    Self {
+      string: arr.iter().map(|byte| format!("{:02x}", byte)).collect(),
+    }
+  }
+
+  // The bytes of the text in hex, the inverse of as_text
+  pub fn from_ascii(text: &str) -> Self {
+    Self {
+      string: text.bytes().map(|byte| format!("{:02x}", byte)).collect(),
+    }
+  }
+
+  // Number of bytes it decodes to. An odd number of digits is completed with a leading zero
+  pub fn len_bytes(&self) -> usize {
+    self.string.len().div_ceil(2)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.string.is_empty()
+  }
+
+  pub fn random(num_bytes: usize) -> Self {
+    let mut rng = thread_rng();
+    Self {
+      string: (0..num_bytes).map(|_| format!("{:02x}", rng.gen::<u8>())).collect(),
+    }
+  }
+}
+
 impl PartialEq for HexString {
   fn eq(&self, other: &Self) -> bool {
     self.string == other.string
@@ -97,7 +132,40 @@ impl HexString {
     Self::try_from(result)
   }
 
+  pub fn xor_repeating<S: AsRef<[u8]>>(&self, key: &S) -> Result<Self, ConversionError> {
+    Self::try_from(repeating_key_xor(self.as_vector_of_bytes()?, key))
+  }
+
+  // Like xor_with for any lengths: the shorter operand is repeated along the longer one, which gives the length.
+  // If one of them is empty there is nothing to cycle, and the other one is returned
+  pub fn xor_with_cycling(&self, key: &Self) -> Result<Self, ConversionError> {
+    let (bytes1, bytes2) = (self.as_vector_of_bytes()?, key.as_vector_of_bytes()?);
+    let (longer, shorter) = if bytes1.len() >= bytes2.len() { (bytes1, bytes2) } else { (bytes2, bytes1) };
+    if shorter.is_empty() {
+      return Self::try_from(longer);
+    }
+    Self::try_from(repeating_key_xor(longer, shorter))
+  }
+
   pub fn as_text(&self) -> Result<String, ConversionError> {
     self.as_binary_string()?.as_text()
   }
+
+  // The bytes read as an integer, with the first byte as the most (be) or least (le) significant one
+  pub fn as_biguint_be(&self) -> Result<BigUint, ConversionError> {
+    Ok(BigUint::from_bytes_be(&self.as_vector_of_bytes()?))
+  }
+
+  pub fn as_biguint_le(&self) -> Result<BigUint, ConversionError> {
+    Ok(BigUint::from_bytes_le(&self.as_vector_of_bytes()?))
+  }
+
+  // Fails with more than 8 bytes, even if the leading ones are zero
+  pub fn as_u64_be(&self) -> Result<u64, ConversionError> {
+    let bytes = self.as_vector_of_bytes()?;
+    if bytes.len() > 8 {
+      return Err(ConversionError::InputTooLong(bytes.len(), 8));
+    }
+    Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+  }
 }
@@ -0,0 +1,51 @@
+// Several ciphertexts (of possibly different lengths) encrypted under the same keystream, as in challenges 19 and 20
+#[derive(Debug, Clone)]
+pub struct InterleavedCiphertexts {
+  ciphertexts: Vec<Vec<u8>>,
+}
+
+impl InterleavedCiphertexts {
+  pub fn new(ciphertexts: Vec<Vec<u8>>) -> Self {
+    Self { ciphertexts }
+  }
+
+  pub fn ciphertexts(&self) -> &[Vec<u8>] {
+    &self.ciphertexts
+  }
+
+  pub fn max_len(&self) -> usize {
+    self.ciphertexts.iter().map(|c| c.len()).max().unwrap_or(0)
+  }
+
+  // Bytes at position i of every ciphertext long enough, all of them XORed with the same keystream byte
+  pub fn column(&self, i: usize) -> Vec<u8> {
+    self.ciphertexts.iter().filter_map(|c| c.get(i).copied()).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_interleaved_ciphertexts_column() {
+    let interleaved = InterleavedCiphertexts::new(vec![
+      vec![1, 2, 3],
+      vec![4, 5, 6, 7, 8],
+      vec![],
+      vec![9, 10],
+    ]);
+    assert_eq!(interleaved.max_len(), 5);
+    assert_eq!(interleaved.column(0), vec![1, 4, 9]);
+    assert_eq!(interleaved.column(2), vec![3, 6]);
+    assert_eq!(interleaved.column(4), vec![8]);
+    assert_eq!(interleaved.column(5), Vec::<u8>::new());
+  }
+
+  #[test]
+  fn test_interleaved_ciphertexts_empty() {
+    let interleaved = InterleavedCiphertexts::new(vec![]);
+    assert_eq!(interleaved.max_len(), 0);
+    assert!(interleaved.column(0).is_empty());
+  }
+}
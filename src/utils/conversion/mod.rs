@@ -1,4 +1,7 @@
 pub mod conversion;
 pub mod hex_string;
 pub mod binary_string;
-pub mod print;
\ No newline at end of file
+pub mod print;
+pub mod interleaved_ciphertexts;
+pub mod der;
+pub mod test_vectors;
\ No newline at end of file
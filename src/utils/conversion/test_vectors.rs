@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::Path;
+
+use super::{conversion::ConversionError, hex_string::HexString};
+
+// One line of a test vector file: name,key,plaintext,ciphertext with the last three in hex
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestVector {
+  pub name: String,
+  pub key: Vec<u8>,
+  pub plaintext: Vec<u8>,
+  pub ciphertext: Vec<u8>,
+}
+
+impl TryFrom<&str> for TestVector {
+  type Error = ConversionError;
+
+  fn try_from(line: &str) -> Result<Self, Self::Error> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [name, key, plaintext, ciphertext] = fields[..] else {
+      return Err(ConversionError::WrongNumberOfFields(fields.len(), 4));
+    };
+    let to_bytes = |hex: &str| HexString::try_from(hex)?.as_vector_of_bytes();
+    Ok(Self {
+      name: name.to_string(),
+      key: to_bytes(key)?,
+      plaintext: to_bytes(plaintext)?,
+      ciphertext: to_bytes(ciphertext)?,
+    })
+  }
+}
+
+// Empty lines and lines starting with # are skipped
+pub fn parse_test_vectors(contents: &str) -> Result<Vec<TestVector>, ConversionError> {
+  contents
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(TestVector::try_from)
+    .collect()
+}
+
+// Panics if the file cannot be read or has a malformed line, since it is meant for tests
+pub fn load_test_vectors<P: AsRef<Path>>(path: P) -> Vec<TestVector> {
+  let path = path.as_ref();
+  let contents = fs::read_to_string(path).unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+  parse_test_vectors(&contents).unwrap_or_else(|e| panic!("Malformed test vector in {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::aes::{aes::AES, utils::AESMode};
+
+  #[test]
+  fn test_load_test_vectors_aes_ecb() {
+    let vectors = load_test_vectors("src/data/aes-ecb-kat.csv");
+    assert_eq!(vectors.len(), 6);
+    for vector in vectors {
+      let ciphertext = AES::encode_ecb_nopad(&vector.plaintext, &vector.key).unwrap();
+      assert_eq!(ciphertext, vector.ciphertext, "{}", vector.name);
+      let plaintext = AES::decode_no_unpad(&ciphertext, &vector.key, AESMode::ECB).unwrap();
+      assert_eq!(plaintext, vector.plaintext, "{}", vector.name);
+    }
+  }
+
+  #[test]
+  fn test_parse_test_vectors_errors() {
+    assert_eq!(parse_test_vectors("# only a comment\n\n"), Ok(vec![]));
+    assert_eq!(parse_test_vectors("a,00,11"), Err(ConversionError::WrongNumberOfFields(3, 4)));
+    assert_eq!(parse_test_vectors("a,00,11,zz"), Err(ConversionError::InvalidHexCharError('z')));
+  }
+}
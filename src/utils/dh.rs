@@ -1,16 +1,89 @@
+use core::fmt;
 use num_bigint::{BigUint, RandBigInt};
-use rand::thread_rng;
+use num_traits::{One, Zero};
+use rand::{thread_rng, Rng};
 use sha2::{Digest, Sha256};
 
-use super::algebra::modulo::mod_exp;
+use super::{
+  aes::{aes::AES, aes_error::AESError, utils::AESMode},
+  algebra::{modulo::mod_exp, primes::get_nist_prime},
+  conversion::conversion::split_digest,
+  mac::{hmac::Sha1HMac, sha1::Sha1},
+};
+
+pub mod ecdh;
 
  // The session is "local" for each party, their params are never sent so they are set to public to be able to access them
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct DiffieHellmanSession {
   pub encryption_key: [u8; 16],
   pub mac_key: [u8; 16],
 }
 
+impl DiffieHellmanSession {
+  /// Derives both keys from SHA256(s), s being the shared secret
+  ///
+  /// ```
+  /// use cryptopals::utils::dh::DiffieHellmanSession;
+  /// use num_bigint::BigUint;
+  ///
+  /// // The session a MITM obtains when it forces s = 1
+  /// let session = DiffieHellmanSession::from_shared_secret(&BigUint::from(1u32));
+  /// assert_eq!(session.encryption_key(), &session.reveal().0);
+  /// assert_ne!(session.encryption_key(), session.mac_key());
+  /// ```
+  pub fn from_shared_secret(s: &BigUint) -> Self {
+    let mut hasher = Sha256::new();
+    hasher.update(s.to_bytes_be());
+    let (encryption_key, mac_key) = split_digest(&hasher.finalize()).expect("SHA-256 digests have 32 bytes");
+    Self {
+      encryption_key,
+      mac_key,
+    }
+  }
+
+  // AES-CBC(SHA1(encryption_key)[0:16], iv, msg), as the parties of challenges 34 and 35 send it
+  pub fn encrypt_message<S: AsRef<[u8]>>(&self, message: &S, iv: &[u8; 16]) -> Vec<u8> {
+    AES::encode(message, &self.message_key(), AESMode::CBC(*iv))
+      .expect("AES-CBC with a 16 byte key cannot fail")
+  }
+
+  // The padding is checked and stripped, as AES::decode does for CBC
+  pub fn decrypt_message<S: AsRef<[u8]>>(&self, ciphertext: &S, iv: &[u8; 16]) -> Result<Vec<u8>, AESError> {
+    AES::decode(ciphertext, &self.message_key(), AESMode::CBC(*iv))
+  }
+
+  fn message_key(&self) -> [u8; 16] {
+    let (key, _) = split_digest::<16, 0>(&Sha1::hash(&self.encryption_key)).expect("SHA-1 digests have 20 bytes");
+    key
+  }
+
+  pub fn encryption_key(&self) -> &[u8; 16] {
+    &self.encryption_key
+  }
+
+  pub fn mac_key(&self) -> &[u8; 16] {
+    &self.mac_key
+  }
+
+  // Returns (encryption_key, mac_key). Debug and Display never print them, so they do not end up in logs
+  pub fn reveal(&self) -> ([u8; 16], [u8; 16]) {
+    (self.encryption_key, self.mac_key)
+  }
+}
+
+impl fmt::Debug for DiffieHellmanSession {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "DiffieHellmanSession {{ .. redacted .. }}")
+  }
+}
+
+impl fmt::Display for DiffieHellmanSession {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "DiffieHellmanSession {{ .. redacted .. }}")
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct DiffieHellmanParty {
   pub p: BigUint,
@@ -21,23 +94,22 @@ pub struct DiffieHellmanParty {
 impl DiffieHellmanParty {
   pub fn new(p: &BigUint, g: &BigUint) -> Self {
     let sk = thread_rng().gen_biguint_below(&p);
-    let pk = mod_exp(&g, &sk, &p);
+    Self::with_secret(p, g, &sk)
+  }
+
+  // Same as new but with a chosen private key, so protocol runs can be reproduced
+  pub fn with_secret(p: &BigUint, g: &BigUint, sk: &BigUint) -> Self {
+    let pk = mod_exp(g, sk, p);
     Self {
       p: p.clone(),
-      sk,
+      sk: sk.clone(),
       pk,
     }
   }
 
   pub fn create_session_with(&self, other_pk: &BigUint) -> DiffieHellmanSession {
     let s = mod_exp(&other_pk, &self.sk, &self.p);
-    let mut hasher = Sha256::new();
-    hasher.update(s.to_bytes_be());
-    let digest = hasher.finalize();
-    DiffieHellmanSession {
-      encryption_key: digest[..16].try_into().unwrap(),
-      mac_key: digest[16..32].try_into().unwrap(),
-    }
+    DiffieHellmanSession::from_shared_secret(&s)
   }
 
   pub fn from_other_party_params(p: &BigUint, g: &BigUint, other_pk: &BigUint) -> (DiffieHellmanParty, DiffieHellmanSession) {
@@ -47,11 +119,90 @@ impl DiffieHellmanParty {
   }
 }
 
+// The generators a MITM injects in Challenge 35: g = 1 and g = p (0 mod p) fix the shared secret to 1 and 0,
+// and g = p - 1 leaves only 1 or p - 1. A party should refuse the parameters if this is true
+pub fn detect_generator_tampering(p: &BigUint, g: &BigUint) -> bool {
+  let g = g % p;
+  g.is_zero() || g.is_one() || g == p - BigUint::one()
+}
+
+// The generator M sends to Bob instead of the real one in Challenge 35
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GTampering {
+  One,
+  P,
+  PMinusOne,
+}
+
+impl GTampering {
+  pub fn injected_g(&self, p: &BigUint) -> BigUint {
+    match self {
+      Self::One => BigUint::one(),
+      Self::P => p.clone(),
+      Self::PMinusOne => p - BigUint::one(),
+    }
+  }
+
+  // Alice computes s = B^a with B = g^b, so s = 1, s = 0 or s = (-1)^(ab) respectively
+  fn possible_secrets(&self, p: &BigUint) -> Vec<BigUint> {
+    match self {
+      Self::One => vec![BigUint::one()],
+      Self::P => vec![BigUint::zero()],
+      Self::PMinusOne => vec![BigUint::one(), p - BigUint::one()],
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MitmResult {
+  pub message: Vec<u8>,
+  // What M decrypted from Alice's message, if anything
+  pub intercepted: Option<Vec<u8>>,
+  // Whether Bob would have refused the tampered parameters with detect_generator_tampering
+  pub hardened_abort: bool,
+}
+
+impl MitmResult {
+  pub fn attacker_decrypted(&self) -> bool {
+    self.intercepted.as_ref() == Some(&self.message)
+  }
+}
+
+/*
+  Challenge 35 from start to end. Alice sends (p, g), M replaces g for Bob, and Bob answers with B = g'^b.
+  Alice sends AES-CBC(SHA1(s)[0:16], iv, msg) + iv with HMAC-SHA1(mac_key, iv || ciphertext). M only has to try
+  the secrets the tampered g allows, and the tag tells him which one was right
+*/
+pub fn simulate_dh_mitm(attack: GTampering) -> MitmResult {
+  let (p, g) = (get_nist_prime(), BigUint::from(2u32));
+  let alice = DiffieHellmanParty::new(&p, &g);
+  let injected_g = attack.injected_g(&p);
+  let bob = DiffieHellmanParty::new(&p, &injected_g);
+  let hardened_abort = detect_generator_tampering(&p, &injected_g);
+
+  let a_session = alice.create_session_with(&bob.pk);
+  let (message, iv): ([u8; 16], [u8; 16]) = (thread_rng().gen(), thread_rng().gen());
+  let ciphertext = a_session.encrypt_message(&message, &iv);
+  let authenticated = [iv.as_slice(), &ciphertext].concat();
+  let tag = Sha1HMac::new(a_session.mac_key()).authenticate(&authenticated);
+
+  let intercepted = attack.possible_secrets(&p).iter().find_map(|s| {
+    let session = DiffieHellmanSession::from_shared_secret(s);
+    Sha1HMac::new(session.mac_key())
+      .verify(&authenticated, tag)
+      .then(|| session.decrypt_message(&ciphertext, &iv).ok())
+      .flatten()
+  });
+  MitmResult {
+    message: message.to_vec(),
+    intercepted,
+    hardened_abort,
+  }
+}
+
 #[cfg(test)]
 mod tests {
 
-  use crate::utils::algebra::primes::get_nist_prime;
-
 use super::*;
 
   #[test]
@@ -84,4 +235,63 @@ use super::*;
     let session_b = bob.create_session_with(&alice.pk);
     assert_eq!(session_a, session_b);
   }
+
+  #[test]
+  fn test_diffie_hellman_with_fixed_secrets() {
+    let (p, g) = (BigUint::from(23u32), BigUint::from(5u32));
+    let alice = DiffieHellmanParty::with_secret(&p, &g, &BigUint::from(6u32));
+    let bob = DiffieHellmanParty::with_secret(&p, &g, &BigUint::from(15u32));
+    assert_eq!(alice.pk, BigUint::from(8u32));
+    assert_eq!(bob.pk, BigUint::from(19u32));
+    let expected = DiffieHellmanSession::from_shared_secret(&BigUint::from(2u32));
+    assert_eq!(alice.create_session_with(&bob.pk), expected);
+    assert_eq!(bob.create_session_with(&alice.pk), expected);
+  }
+
+  #[test]
+  fn test_message_round_trip_between_parties() {
+    let (p, g) = (get_nist_prime(), BigUint::from(2u32));
+    let alice = DiffieHellmanParty::new(&p, &g);
+    let (bob, b_session) = DiffieHellmanParty::from_other_party_params(&p, &g, &alice.pk);
+    let a_session = alice.create_session_with(&bob.pk);
+    let iv = [7u8; 16];
+    let message = b"BOCA YO TE AMO, SOS MI VIDA!!!!!";
+    let ciphertext = a_session.encrypt_message(message, &iv);
+    assert_ne!(ciphertext, message.to_vec());
+    assert_eq!(b_session.decrypt_message(&ciphertext, &iv).unwrap(), message);
+    let other_session = DiffieHellmanSession::from_shared_secret(&BigUint::from(1u32));
+    assert_ne!(other_session.decrypt_message(&ciphertext, &iv).ok(), Some(message.to_vec()));
+  }
+
+  #[test]
+  fn test_diffie_hellman_session_is_redacted() {
+    let session = DiffieHellmanSession {
+      encryption_key: [0xab; 16],
+      mac_key: [0xcd; 16],
+    };
+    for output in [format!("{:?}", session), format!("{}", session)] {
+      assert_eq!(output, "DiffieHellmanSession { .. redacted .. }");
+      assert!(!output.contains("171") && !output.contains("205"));
+      assert!(!output.to_lowercase().contains("abab") && !output.to_lowercase().contains("cdcd"));
+    }
+    assert_eq!(session.reveal(), ([0xab; 16], [0xcd; 16]));
+  }
+
+  #[test]
+  fn test_detect_generator_tampering() {
+    let p = get_nist_prime();
+    for g in [BigUint::zero(), BigUint::one(), p.clone(), &p - BigUint::one(), &p + BigUint::one()] {
+      assert!(detect_generator_tampering(&p, &g));
+    }
+    assert!(!detect_generator_tampering(&p, &BigUint::from(2u32)));
+  }
+
+  #[test]
+  fn test_simulate_dh_mitm() {
+    for attack in [GTampering::One, GTampering::P, GTampering::PMinusOne] {
+      let result = simulate_dh_mitm(attack);
+      assert!(result.attacker_decrypted(), "{attack:?}");
+      assert!(result.hardened_abort, "{attack:?}");
+    }
+  }
 }
@@ -0,0 +1,164 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::thread_rng;
+
+use super::DiffieHellmanSession;
+use crate::utils::algebra::modulo::inv_mod;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EcPoint {
+  Infinity,
+  Affine(BigUint, BigUint),
+}
+
+// y^2 = x^3 + ax + b over F_p, with a generator g of prime order n
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeierstrassCurve {
+  pub p: BigUint,
+  pub a: BigUint,
+  pub b: BigUint,
+  pub g: EcPoint,
+  pub n: BigUint,
+}
+
+impl WeierstrassCurve {
+  // NIST P-256 (FIPS 186-4, D.1.2.3)
+  pub fn p256() -> Self {
+    let hex = |s: &str| BigUint::parse_bytes(s.as_bytes(), 16).unwrap();
+    let p = hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+    Self {
+      a: &p - BigUint::from(3u8),
+      b: hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+      g: EcPoint::Affine(
+        hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+        hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+      ),
+      n: hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+      p,
+    }
+  }
+
+  pub fn contains(&self, point: &EcPoint) -> bool {
+    match point {
+      EcPoint::Infinity => true,
+      EcPoint::Affine(x, y) => {
+        x < &self.p && y < &self.p && (y * y) % &self.p == (x * x * x + &self.a * x + &self.b) % &self.p
+      }
+    }
+  }
+
+  fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+    (x + &self.p - y % &self.p) % &self.p
+  }
+
+  // Adds two points of the curve with the chord rule, or the tangent rule when they are equal
+  pub fn add(&self, p1: &EcPoint, p2: &EcPoint) -> EcPoint {
+    let (x1, y1, x2, y2) = match (p1, p2) {
+      (EcPoint::Infinity, _) => return p2.clone(),
+      (_, EcPoint::Infinity) => return p1.clone(),
+      (EcPoint::Affine(x1, y1), EcPoint::Affine(x2, y2)) => (x1, y1, x2, y2),
+    };
+    if x1 == x2 {
+      // P + (-P) = O, and also 2P = O when y = 0
+      return if y1 == y2 && !y1.is_zero() { self.double(p1) } else { EcPoint::Infinity };
+    }
+    let lambda = (self.sub(y2, y1) * inv_mod(&self.sub(x2, x1), &self.p).unwrap()) % &self.p;
+    self.point_from_slope(&lambda, x1, y1, x2)
+  }
+
+  pub fn double(&self, point: &EcPoint) -> EcPoint {
+    let (x, y) = match point {
+      EcPoint::Affine(x, y) if !y.is_zero() => (x, y),
+      _ => return EcPoint::Infinity,
+    };
+    let numerator = (BigUint::from(3u8) * x * x + &self.a) % &self.p;
+    let lambda = (numerator * inv_mod(&((BigUint::from(2u8) * y) % &self.p), &self.p).unwrap()) % &self.p;
+    self.point_from_slope(&lambda, x, y, x)
+  }
+
+  // x3 = lambda^2 - x1 - x2, y3 = lambda (x1 - x3) - y1
+  fn point_from_slope(&self, lambda: &BigUint, x1: &BigUint, y1: &BigUint, x2: &BigUint) -> EcPoint {
+    let x3 = self.sub(&self.sub(&(lambda * lambda), x1), x2);
+    let y3 = self.sub(&(lambda * self.sub(x1, &x3)), y1);
+    EcPoint::Affine(x3, y3)
+  }
+
+  // Double-and-add, from the most significant bit of k
+  pub fn scalar_mul(&self, k: &BigUint, point: &EcPoint) -> EcPoint {
+    (0..k.bits()).rev().fold(EcPoint::Infinity, |acc, i| {
+      let doubled = self.double(&acc);
+      if k.bit(i) {
+        self.add(&doubled, point)
+      } else {
+        doubled
+      }
+    })
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct EcdhParty {
+  pub curve: WeierstrassCurve,
+  sk: BigUint,
+  pub pk: EcPoint,
+}
+
+impl EcdhParty {
+  pub fn new(curve: &WeierstrassCurve) -> Self {
+    let sk = thread_rng().gen_biguint_range(&BigUint::one(), &curve.n);
+    Self::with_secret(curve, &sk)
+  }
+
+  // Same as new but with a chosen private key, so protocol runs can be reproduced
+  pub fn with_secret(curve: &WeierstrassCurve, sk: &BigUint) -> Self {
+    Self {
+      pk: curve.scalar_mul(sk, &curve.g),
+      curve: curve.clone(),
+      sk: sk.clone(),
+    }
+  }
+
+  // The session keys come from SHA256(x), x being the coordinate of the shared point.
+  // None if other_pk is not on the curve or the shared point is the point at infinity
+  pub fn create_session_with(&self, other_pk: &EcPoint) -> Option<DiffieHellmanSession> {
+    if !self.curve.contains(other_pk) {
+      return None;
+    }
+    match self.curve.scalar_mul(&self.sk, other_pk) {
+      EcPoint::Affine(x, _) => Some(DiffieHellmanSession::from_shared_secret(&x)),
+      EcPoint::Infinity => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_p256_scalar_mul() {
+    let curve = WeierstrassCurve::p256();
+    assert!(curve.contains(&curve.g));
+    let hex = |s: &str| BigUint::parse_bytes(s.as_bytes(), 16).unwrap();
+    let expected = EcPoint::Affine(
+      hex("7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978"),
+      hex("07775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1"),
+    );
+    assert_eq!(curve.scalar_mul(&BigUint::from(2u8), &curve.g), expected);
+    assert_eq!(curve.add(&curve.g, &curve.g), expected);
+    assert_eq!(curve.scalar_mul(&curve.n, &curve.g), EcPoint::Infinity);
+  }
+
+  #[test]
+  fn test_ecdh() {
+    let curve = WeierstrassCurve::p256();
+    let alice = EcdhParty::new(&curve);
+    let bob = EcdhParty::new(&curve);
+    let session_a = alice.create_session_with(&bob.pk).unwrap();
+    let session_b = bob.create_session_with(&alice.pk).unwrap();
+    assert_eq!(session_a, session_b);
+    let off_curve = EcPoint::Affine(BigUint::one(), BigUint::one());
+    assert_eq!(alice.create_session_with(&off_curve), None);
+    assert_eq!(alice.create_session_with(&EcPoint::Infinity), None);
+  }
+}
@@ -1,18 +1,42 @@
-use num_bigint::{BigUint, RandBigInt};
-use num_traits::{One, Zero};
+use num::Integer;
+use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+use num_traits::{One, Signed, Zero};
 use rand::thread_rng;
 
 use super::{
-  algebra::modulo::{inv_mod, mod_exp},
-  mac::sha1::Sha1,
+  algebra::{
+    lattice::lll_reduce,
+    modulo::{inv_mod, mod_exp},
+  },
+  conversion::{
+    conversion::left_pad_to,
+    der::{der_decode_element, der_decode_integer, der_encode_integer, der_encode_sequence, DER_SEQUENCE_TAG},
+  },
+  mac::{hmac::Sha1HMac, sha1::Sha1, sha256::Sha256},
 };
 
 // p,q,g are public parameters. (x,y) is the key pair in DSA
+// strict_params makes verify reject degenerate generators (g = 0 or g = 1 mod p), see challenge 45
 #[derive(Clone)]
 pub struct DSA {
   pub p: BigUint,
   pub q: BigUint,
   pub g: BigUint,
+  pub strict_params: bool,
+  pub config: DsaConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DsaHash {
+  #[default]
+  Sha1,
+  Sha256,
+}
+
+// Hash used for the messages, SHA-1 unless configured otherwise
+#[derive(Debug, Clone, Default)]
+pub struct DsaConfig {
+  pub hash: DsaHash,
 }
 
 pub trait SignatureAlgorithm {
@@ -49,7 +73,13 @@ impl SignatureAlgorithm for DSA {
     .unwrap();
     assert_eq!((&p - BigUint::one()) % &q, BigUint::zero()); // q | p - 1
     assert_eq!(mod_exp(&g, &q, &p), BigUint::one());
-    Self { p, q, g }
+    Self {
+      p,
+      q,
+      g,
+      strict_params: false,
+      config: DsaConfig::default(),
+    }
   }
 
   fn get_params(&self) -> (Self::FieldElement, Self::FieldElement, Self::FieldElement) {
@@ -69,7 +99,7 @@ impl SignatureAlgorithm for DSA {
     message: &S,
   ) -> (Self::FieldElement, Self::FieldElement) {
     let (mut r, mut s) = (BigUint::zero(), BigUint::zero());
-    let h = BigUint::from_bytes_be(&Sha1::hash(message)) % &self.q;
+    let h = self.hash_to_int(message);
     while r.is_zero() || s.is_zero() {
       let k = thread_rng().gen_biguint_range(&BigUint::from(2u8), &self.q);
       r = mod_exp(&self.g, &k, &self.p) % &self.q;
@@ -89,11 +119,17 @@ impl SignatureAlgorithm for DSA {
     signature: &(Self::FieldElement, Self::FieldElement),
   ) -> bool {
     let (r, s) = signature;
-    if s.is_zero() || r >= &self.q || s >= &self.q {
+    if r.is_zero() || s.is_zero() || r >= &self.q || s >= &self.q {
+      return false;
+    }
+    if self.strict_params && self.has_weak_params() {
       return false;
     }
-    let w = inv_mod(s, &self.q).unwrap(); // w = s^-1 (mod q)
-    let h = BigUint::from_bytes_be(&Sha1::hash(message)) % &self.q;
+    // w = s^-1 (mod q). It may not exist if q is composite, and a crafted signature must not make verify panic
+    let Some(w) = inv_mod(s, &self.q) else {
+      return false;
+    };
+    let h = self.hash_to_int(message);
     let u1 = (&h * &w) % &self.q; // u1 = H(m) * w (mod q)
     let u2 = (r * &w) % &self.q; // u2 = r * w (mod q)
     let v = {
@@ -106,6 +142,180 @@ impl SignatureAlgorithm for DSA {
   }
 }
 
+impl DSA {
+  pub fn with_config(mut self, config: DsaConfig) -> Self {
+    self.config = config;
+    self
+  }
+
+  pub fn digest<S: AsRef<[u8]>>(&self, message: &S) -> Vec<u8> {
+    match self.config.hash {
+      DsaHash::Sha1 => Sha1::hash(message).to_vec(),
+      DsaHash::Sha256 => Sha256::hash(message).to_vec(),
+    }
+  }
+
+  // As FIPS 186-4 says, the leftmost min(N, outlen) bits of the digest, where N is the bit length of q
+  pub fn hash_to_int<S: AsRef<[u8]>>(&self, message: &S) -> BigUint {
+    let digest = self.digest(message);
+    let (out_len, n) = (8 * digest.len() as u64, self.q.bits());
+    let z = BigUint::from_bytes_be(&digest);
+    let z = if out_len > n { z >> (out_len - n) } else { z };
+    z % &self.q
+  }
+
+  // g = 0 (mod p) makes r = 0 for every k, g = 1 (mod p) makes g^u1 vanish from the verification
+  pub fn has_weak_params(&self) -> bool {
+    let g = &self.g % &self.p;
+    g.is_zero() || g.is_one()
+  }
+
+  /*
+    Only valid when g = 1 (mod p), e.g. g = p + 1. Choose z, r = (y^z mod p) mod q, s = r/z (mod q). Then:
+      w = s^-1 = z/r (mod q)
+      v = (g^u1 y^u2 mod p) mod q = (y^(rw) mod p) mod q = (y^z mod p) mod q = r
+    The signature is valid for every message.
+  */
+  pub fn forge_magic_signature(&self, y: &BigUint, z: &BigUint) -> (BigUint, BigUint) {
+    let r = mod_exp(y, z, &self.p) % &self.q;
+    let s = (&r * inv_mod(z, &self.q).unwrap()) % &self.q;
+    (r, s)
+  }
+
+  // Signs with a chosen nonce k, as a broken signer would. Returns None if k gives r = 0 or s = 0
+  pub fn sign_with_nonce<S: AsRef<[u8]>>(&self, x: &BigUint, message: &S, k: &BigUint) -> Option<(BigUint, BigUint)> {
+    let h = self.hash_to_int(message);
+    let r = mod_exp(&self.g, k, &self.p) % &self.q;
+    let s = (inv_mod(k, &self.q)? * (&h + x * &r)) % &self.q;
+    if r.is_zero() || s.is_zero() {
+      return None;
+    }
+    Some((r, s))
+  }
+
+  /*
+    RFC 6979 deterministic nonce, with HMAC-SHA1 keyed by the private key and the message hash:
+      V = 0x01..01, K = 0x00..00
+      K = HMAC_K(V || 0x00 || int2octets(x) || bits2octets(h1)), V = HMAC_K(V)
+      K = HMAC_K(V || 0x01 || int2octets(x) || bits2octets(h1)), V = HMAC_K(V)
+    Then V = HMAC_K(V) is concatenated until there are qlen bits, and its leftmost qlen bits are the candidate k.
+    If k is not in [1, q-1] or gives r = 0 or s = 0: K = HMAC_K(V || 0x00), V = HMAC_K(V) and try again.
+    The same (x, message) always gives the same signature
+  */
+  pub fn sign_deterministic<S: AsRef<[u8]>>(&self, x: &BigUint, message: &S) -> (BigUint, BigUint) {
+    let q_len = self.q.bits();
+    let r_len = q_len.div_ceil(8) as usize;
+    // bits2int keeps the leftmost q_len bits
+    let bits2int = |bytes: &[u8]| {
+      let n = BigUint::from_bytes_be(bytes);
+      let b_len = 8 * bytes.len() as u64;
+      if b_len > q_len { n >> (b_len - q_len) } else { n }
+    };
+    let int2octets = |n: &BigUint| left_pad_to(n.to_bytes_be(), r_len).unwrap();
+    let h1 = self.digest(message);
+    let (x_octets, h_octets) = (int2octets(x), int2octets(&(bits2int(&h1) % &self.q)));
+
+    let mut v = [0x01u8; 20];
+    let mut k = [0x00u8; 20];
+    for separator in [0x00u8, 0x01] {
+      k = Sha1HMac::new(&k).authenticate_chunks([&v[..], &[separator], &x_octets, &h_octets]);
+      v = Sha1HMac::new(&k).authenticate(&v);
+    }
+    loop {
+      let mut t = Vec::with_capacity(r_len);
+      while t.len() < r_len {
+        v = Sha1HMac::new(&k).authenticate(&v);
+        t.extend_from_slice(&v);
+      }
+      let nonce = bits2int(&t[..r_len]);
+      if !nonce.is_zero() && nonce < self.q {
+        if let Some(signature) = self.sign_with_nonce(x, message, &nonce) {
+          return signature;
+        }
+      }
+      k = Sha1HMac::new(&k).authenticate_chunks([&v[..], &[0x00]]);
+      v = Sha1HMac::new(&k).authenticate(&v);
+    }
+  }
+
+  /*
+    Hidden Number Problem. Every nonce is k = known_msb * 2^unknown_bits + b, with 0 <= b < B = 2^unknown_bits.
+    From s k = h + x r (mod q):
+      b = t x + u (mod q), with t = r s^-1 and u = h s^-1 - known_msb * 2^unknown_bits
+    So for some integers c_i, (b_1, ..., b_n, x B / q, B) = x T + U - sum c_i q e_i is a short vector of the lattice
+    spanned by the rows (scaled by q to make them integers)
+      q^2 e_i for i = 1..n
+      T = (q t_1, ..., q t_n, B, 0)
+      U = (q u_1, ..., q u_n, 0, q B)
+    LLL finds it when the signatures leak enough bits in total, roughly n * known bits > bits of q plus some margin.
+    Returns None if no candidate matches the public key y
+  */
+  pub fn recover_key_from_partial_nonces(
+    &self,
+    y: &BigUint,
+    signatures: &[PartialNonceSignature],
+    unknown_bits: u64,
+  ) -> Option<BigUint> {
+    let n = signatures.len();
+    let q = self.q.to_bigint().unwrap();
+    let bound = BigInt::one() << unknown_bits;
+    let mut basis: Vec<Vec<BigInt>> = vec![vec![BigInt::zero(); n + 2]; n + 2];
+    for (i, signature) in signatures.iter().enumerate() {
+      let s_inv = inv_mod(&signature.s, &self.q)?;
+      let t = (&signature.r * &s_inv) % &self.q;
+      let known = (&signature.known_msb << unknown_bits) % &self.q;
+      let u = ((&signature.h % &self.q) * &s_inv + &self.q - known) % &self.q;
+      basis[i][i] = &q * &q;
+      basis[n][i] = &q * t.to_bigint().unwrap();
+      basis[n + 1][i] = &q * u.to_bigint().unwrap();
+    }
+    basis[n][n] = bound.clone();
+    basis[n + 1][n + 1] = &q * &bound;
+
+    lll_reduce(&basis).into_iter().find_map(|row| {
+      if row[n + 1].abs() != &q * &bound {
+        return None;
+      }
+      // The row may be the negated target vector
+      let x_bound = if row[n + 1].is_positive() { row[n].clone() } else { -row[n].clone() };
+      if !(&x_bound % &bound).is_zero() {
+        return None;
+      }
+      let x = (x_bound / &bound).mod_floor(&q);
+      let x = x.to_biguint().unwrap();
+      (mod_exp(&self.g, &x, &self.p) == *y).then_some(x)
+    })
+  }
+}
+
+// A signature over a message with hash h whose nonce starts with the bits of known_msb
+#[derive(Debug, Clone)]
+pub struct PartialNonceSignature {
+  pub h: BigUint,
+  pub r: BigUint,
+  pub s: BigUint,
+  pub known_msb: BigUint,
+}
+
+// DER encoding of the signature as SEQUENCE { INTEGER r, INTEGER s }
+pub fn encode_signature_der(signature: &(BigUint, BigUint)) -> Vec<u8> {
+  let (r, s) = signature;
+  der_encode_sequence(&[der_encode_integer(r), der_encode_integer(s)])
+}
+
+pub fn decode_signature_der(bytes: &[u8]) -> Option<(BigUint, BigUint)> {
+  let (contents, rest) = der_decode_element(bytes, DER_SEQUENCE_TAG)?;
+  if !rest.is_empty() {
+    return None;
+  }
+  let (r, contents) = der_decode_integer(contents)?;
+  let (s, contents) = der_decode_integer(contents)?;
+  if !contents.is_empty() {
+    return None;
+  }
+  Some((r, s))
+}
+
 /*
   Correctness: Suppose r = (g^k mod p) mod q, s = k^-1 (H(m) + xr) (mod q) are correct. Then:
     v = (g^u1 y^u2 mod p) mod q
@@ -130,6 +340,30 @@ mod tests {
     assert!(dsa.verify(&y, message, &(r, s)))
   }
 
+  #[test]
+  fn test_dsa_sign_deterministic() {
+    let dsa = DSA::with_default_params();
+    let (x, y) = dsa.generate_keys();
+    let message = b"AGUANTE BOQUITA PAPA";
+    let signature = dsa.sign_deterministic(&x, message);
+    assert_eq!(dsa.sign_deterministic(&x, message), signature);
+    assert!(dsa.verify(&y, message, &signature));
+    assert_ne!(dsa.sign_deterministic(&x, b"AGUANTE RIVER PAPA"), signature);
+  }
+
+  #[test]
+  fn test_dsa_with_sha256() {
+    let dsa = DSA::with_default_params().with_config(DsaConfig { hash: DsaHash::Sha256 });
+    let (x, y) = dsa.generate_keys();
+    let message = b"AGUANTE BOQUITA PAPA";
+    let signature = dsa.sign(&x, message);
+    assert!(dsa.verify(&y, message, &signature));
+    assert!(!DSA::with_default_params().verify(&y, message, &signature));
+    // The 256 bit digest is truncated to the 160 bits of q
+    assert_eq!(dsa.hash_to_int(message), BigUint::from_bytes_be(&Sha256::hash(message)[..20]) % &dsa.q);
+    assert!(dsa.verify(&y, message, &dsa.sign_deterministic(&x, message)));
+  }
+
   #[test]
   #[should_panic]
   fn test_dsa_does_not_verify_invalid_signature() {
@@ -139,4 +373,97 @@ mod tests {
     let (r, s) = dsa.sign(&x, message);
     assert!(dsa.verify(&y, message, &(r, s + BigUint::one())))
   }
+
+  #[test]
+  fn test_dsa_rejects_zero_r() {
+    let mut dsa = DSA::with_default_params();
+    let (_, y) = dsa.generate_keys();
+    dsa.g = BigUint::zero();
+    let forged_signature = (BigUint::zero(), BigUint::from(123456789u32));
+    assert!(!dsa.verify(&y, b"AGUANTE BOQUITA PAPA", &forged_signature));
+  }
+
+  #[test]
+  fn test_dsa_rejects_degenerate_signatures() {
+    let mut dsa = DSA::with_default_params();
+    let (x, y) = dsa.generate_keys();
+    let message = b"AGUANTE BOQUITA PAPA";
+    let (r, s) = dsa.sign(&x, message);
+    assert!(!dsa.verify(&y, message, &(BigUint::zero(), s.clone())));
+    assert!(!dsa.verify(&y, message, &(r.clone(), BigUint::zero())));
+    assert!(!dsa.verify(&y, message, &(r.clone(), dsa.q.clone())));
+
+    // With a composite q, s = 3 has no inverse
+    dsa.q = &dsa.q * BigUint::from(3u8);
+    assert!(!dsa.verify(&y, message, &(r, BigUint::from(3u8))));
+  }
+
+  #[test]
+  fn test_dsa_magic_signature() {
+    let mut dsa = DSA::with_default_params();
+    let (_, y) = dsa.generate_keys();
+    dsa.g = &dsa.p + BigUint::one();
+    assert!(dsa.has_weak_params());
+    let signature = dsa.forge_magic_signature(&y, &BigUint::from(234567u32));
+    assert!(dsa.verify(&y, b"Hello, world", &signature));
+    assert!(dsa.verify(&y, b"Goodbye, world", &signature));
+
+    dsa.strict_params = true;
+    assert!(!dsa.verify(&y, b"Hello, world", &signature));
+    assert!(!dsa.verify(&y, b"Goodbye, world", &signature));
+  }
+
+  #[test]
+  fn test_dsa_signature_der_round_trip() {
+    let dsa = DSA::with_default_params();
+    let (x, y) = dsa.generate_keys();
+    let message = b"AGUANTE BOQUITA PAPA";
+    let signature = dsa.sign(&x, message);
+    let der = encode_signature_der(&signature);
+    let decoded = decode_signature_der(&der).unwrap();
+    assert_eq!(decoded, signature);
+    assert!(dsa.verify(&y, message, &decoded));
+  }
+
+  #[test]
+  fn test_dsa_signature_der_encoding() {
+    let signature = (BigUint::one(), BigUint::from(128u32));
+    let der = encode_signature_der(&signature);
+    assert_eq!(der, vec![0x30, 0x07, 0x02, 0x01, 0x01, 0x02, 0x02, 0x00, 0x80]);
+    assert_eq!(decode_signature_der(&der), Some(signature));
+
+    let big = (BigUint::one() << 1200u32, BigUint::from(5u32));
+    assert_eq!(decode_signature_der(&encode_signature_der(&big)), Some(big));
+
+    assert_eq!(decode_signature_der(&der[..8]), None); // truncated
+    assert_eq!(decode_signature_der(&[der.clone(), vec![0x00]].concat()), None); // trailing bytes
+    assert_eq!(decode_signature_der(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x80]), None); // negative
+    assert_eq!(decode_signature_der(&[0x30, 0x07, 0x02, 0x02, 0x00, 0x01, 0x02, 0x01, 0x01]), None); // non-minimal
+    assert_eq!(decode_signature_der(&[0x30, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]), None); // huge length
+  }
+
+  #[test]
+  fn test_recover_key_from_partial_nonces() {
+    let dsa = DSA::with_default_params();
+    let (x, y) = dsa.generate_keys();
+    // 64 of the 160 bits of every nonce are known, 5 signatures leak 320 bits in total
+    let unknown_bits = 96;
+    let signatures: Vec<PartialNonceSignature> = (0..5u8)
+      .map(|i| {
+        let message = [b"HIDDEN NUMBER ".as_slice(), &[i]].concat();
+        let k = thread_rng().gen_biguint_range(&BigUint::from(2u8), &dsa.q);
+        let (r, s) = dsa.sign_with_nonce(&x, &message, &k).unwrap();
+        assert!(dsa.verify(&y, &message, &(r.clone(), s.clone())));
+        PartialNonceSignature {
+          h: BigUint::from_bytes_be(&Sha1::hash(&message)),
+          r,
+          s,
+          known_msb: k >> unknown_bits,
+        }
+      })
+      .collect();
+    assert_eq!(dsa.recover_key_from_partial_nonces(&y, &signatures, unknown_bits), Some(x));
+    // Two signatures do not leak enough
+    assert_eq!(dsa.recover_key_from_partial_nonces(&y, &signatures[..2], unknown_bits), None);
+  }
 }
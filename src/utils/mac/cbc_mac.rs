@@ -0,0 +1,122 @@
+use crate::utils::{
+  aes::{
+    aes::AES,
+    constants::AES_BLOCK_SIZE,
+    utils::{pkcs_padding, AESMode},
+  },
+  conversion::conversion::xor_bytes_vectors,
+};
+
+// CBC-MAC(K, IV, P) is the last block of AES-CBC(K, IV, P).
+// P is padded only when it does not fill its last block, so the attacks of challenges 49 and 50 can use aligned messages
+pub fn cbc_mac<S: AsRef<[u8]>, T: AsRef<[u8]>>(msg: &S, key: &T, iv: [u8; 16]) -> [u8; 16] {
+  let padded_msg = pad_last_block(msg);
+  // AES::encode pads again, that extra block is not part of the MAC
+  let cip = AES::encode(&padded_msg, &key, AESMode::CBC(iv)).unwrap();
+  cip[padded_msg.len() - AES_BLOCK_SIZE..padded_msg.len()].try_into().unwrap()
+}
+
+fn pad_last_block<S: AsRef<[u8]>>(msg: &S) -> Vec<u8> {
+  let msg = msg.as_ref();
+  if !msg.is_empty() && msg.len().is_multiple_of(AES_BLOCK_SIZE) {
+    msg.to_vec()
+  } else {
+    pkcs_padding(&msg, AES_BLOCK_SIZE as u8)
+  }
+}
+
+// If the IV travels with the message, P1 ^ IV is all the first block cipher call sees.
+// Swapping the bytes at positions i and j of P1 is compensated by xoring both IV[i] and IV[j] with P1[i] ^ P1[j],
+// so the returned (message, IV) pair has the same CBC-MAC as the original one (Challenge 49, part 1)
+pub fn forge_cbc_mac_iv<S: AsRef<[u8]>>(
+  original_msg: &S,
+  original_iv: &[u8; 16],
+  byte_swaps: &[(usize, usize)],
+) -> (Vec<u8>, [u8; 16]) {
+  let mut msg = original_msg.as_ref().to_vec();
+  let mut iv = *original_iv;
+  for &(i, j) in byte_swaps {
+    assert!(i < AES_BLOCK_SIZE && j < AES_BLOCK_SIZE, "Only bytes of the first block can be swapped");
+    let diff = msg[i] ^ msg[j];
+    iv[i] ^= diff;
+    iv[j] ^= diff;
+    msg.swap(i, j);
+  }
+  (msg, iv)
+}
+
+// With a fixed IV, the CBC state after the (padded) victim message is its MAC. Xoring the attacker's first block
+// with it makes the next cipher call see exactly what it saw when the attacker message was signed on its own.
+// So CBC-MAC(victim_msg || padding || forged block || rest of attacker_msg) = CBC-MAC(attacker_msg) (Challenge 49, part 2)
+pub fn forge_cbc_mac_extension<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+  victim_msg: &S,
+  victim_mac: &[u8; 16],
+  attacker_msg: &T,
+) -> Vec<u8> {
+  let padded_attacker_msg = pad_last_block(attacker_msg);
+  let forged_block = xor_bytes_vectors(&padded_attacker_msg[..AES_BLOCK_SIZE], victim_mac).unwrap();
+  let rest = &attacker_msg.as_ref()[AES_BLOCK_SIZE.min(attacker_msg.as_ref().len())..];
+  [
+    pad_last_block(victim_msg).as_ref(),
+    forged_block.as_ref(),
+    rest,
+  ]
+  .concat()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_forge_cbc_mac_iv_swaps_accounts() {
+    let key = b"YELLOW SUBMARINE";
+    let iv = [0x42; 16];
+    let msg = b"from=1&to=2&amount=1000000";
+    let mac = cbc_mac(msg, key, iv);
+
+    let (forged_msg, forged_iv) = forge_cbc_mac_iv(msg, &iv, &[(5, 10)]);
+    assert_eq!(forged_msg, b"from=2&to=1&amount=1000000");
+    assert_ne!(forged_iv, iv);
+    assert_eq!(cbc_mac(&forged_msg, key, forged_iv), mac);
+    assert_ne!(cbc_mac(&forged_msg, key, iv), mac);
+  }
+
+  #[test]
+  fn test_forge_cbc_mac_iv_multiple_swaps() {
+    let key = b"YELLOW SUBMARINE";
+    let iv = [0; 16];
+    let msg = b"from=12&to=34&amount=5";
+    let mac = cbc_mac(msg, key, iv);
+    let (forged_msg, forged_iv) = forge_cbc_mac_iv(msg, &iv, &[(5, 11), (6, 12)]);
+    assert_eq!(forged_msg, b"from=34&to=12&amount=5");
+    assert_eq!(cbc_mac(&forged_msg, key, forged_iv), mac);
+  }
+
+  #[test]
+  fn test_forge_cbc_mac_extension() {
+    let key = b"YELLOW SUBMARINE";
+    let iv = [0; 16];
+    let victim_msg = b"from=2&tx_list=3:100";
+    let victim_mac = cbc_mac(victim_msg, key, iv);
+    let attacker_msg = b"from=1&tx_list=3:1;1:1000000";
+    let attacker_mac = cbc_mac(attacker_msg, key, iv);
+
+    let forged_msg = forge_cbc_mac_extension(victim_msg, &victim_mac, attacker_msg);
+    assert!(forged_msg.starts_with(victim_msg));
+    assert!(forged_msg.ends_with(b";1:1000000"));
+    assert_eq!(cbc_mac(&forged_msg, key, iv), attacker_mac);
+  }
+
+  #[test]
+  fn test_forge_cbc_mac_extension_short_attacker_msg() {
+    let key = b"YELLOW SUBMARINE";
+    let iv = [0; 16];
+    let victim_msg = b"from=2&tx_list=3:100;4:200";
+    let victim_mac = cbc_mac(victim_msg, key, iv);
+    let attacker_msg = b";1:1000000";
+    let forged_msg = forge_cbc_mac_extension(victim_msg, &victim_mac, attacker_msg);
+    assert_eq!(forged_msg.len(), 48);
+    assert_eq!(cbc_mac(&forged_msg, key, iv), cbc_mac(attacker_msg, key, iv));
+  }
+}
@@ -0,0 +1,88 @@
+use super::{
+  md4::{MD4Digest, MD4, MD4_BLOCK_SIZE},
+  sha1::{Sha1, Sha1Digest, SHA1_BLOCK_SIZE},
+  sha256::{Sha256, Sha256Digest, SHA256_BLOCK_SIZE},
+};
+
+// Metadata the generic constructions (HMAC, HKDF) need from the underlying hash,
+// and the incremental interface HMAC feeds its inner hash with
+pub trait HashFunction {
+  const BLOCK_SIZE: usize;
+  const OUTPUT_SIZE: usize;
+  type Digest: AsRef<[u8]>;
+  fn digest(data: &[u8]) -> Vec<u8>;
+  fn new() -> Self;
+  fn update(&mut self, data: &[u8]);
+  fn finalize(&mut self) -> Self::Digest;
+}
+
+impl HashFunction for Sha1 {
+  const BLOCK_SIZE: usize = SHA1_BLOCK_SIZE;
+  const OUTPUT_SIZE: usize = 20;
+  type Digest = Sha1Digest;
+  fn digest(data: &[u8]) -> Vec<u8> {
+    Sha1::hash(&data).to_vec()
+  }
+  fn new() -> Self {
+    Sha1::new()
+  }
+  fn update(&mut self, data: &[u8]) {
+    Sha1::update(self, &data)
+  }
+  fn finalize(&mut self) -> Sha1Digest {
+    Sha1::finalize(self)
+  }
+}
+
+impl HashFunction for Sha256 {
+  const BLOCK_SIZE: usize = SHA256_BLOCK_SIZE;
+  const OUTPUT_SIZE: usize = 32;
+  type Digest = Sha256Digest;
+  fn digest(data: &[u8]) -> Vec<u8> {
+    Sha256::hash(&data).to_vec()
+  }
+  fn new() -> Self {
+    Sha256::new()
+  }
+  fn update(&mut self, data: &[u8]) {
+    Sha256::update(self, &data)
+  }
+  fn finalize(&mut self) -> Sha256Digest {
+    Sha256::finalize(self)
+  }
+}
+
+impl HashFunction for MD4 {
+  const BLOCK_SIZE: usize = MD4_BLOCK_SIZE;
+  const OUTPUT_SIZE: usize = 16;
+  type Digest = MD4Digest;
+  fn digest(data: &[u8]) -> Vec<u8> {
+    MD4::hash(&data).to_vec()
+  }
+  fn new() -> Self {
+    MD4::new()
+  }
+  fn update(&mut self, data: &[u8]) {
+    MD4::update(self, &data)
+  }
+  fn finalize(&mut self) -> MD4Digest {
+    MD4::finalize(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Checked at compile time
+  const _: () = assert!(<Sha1 as HashFunction>::BLOCK_SIZE == 64 && <Sha1 as HashFunction>::OUTPUT_SIZE == 20);
+  const _: () = assert!(<Sha256 as HashFunction>::BLOCK_SIZE == 64 && <Sha256 as HashFunction>::OUTPUT_SIZE == 32);
+  const _: () = assert!(<MD4 as HashFunction>::BLOCK_SIZE == 64 && <MD4 as HashFunction>::OUTPUT_SIZE == 16);
+
+  #[test]
+  fn test_output_size_matches_digest() {
+    assert_eq!(<Sha1 as HashFunction>::digest(b"abc").len(), <Sha1 as HashFunction>::OUTPUT_SIZE);
+    assert_eq!(<Sha256 as HashFunction>::digest(b"abc").len(), <Sha256 as HashFunction>::OUTPUT_SIZE);
+    assert_eq!(<MD4 as HashFunction>::digest(b"abc").len(), <MD4 as HashFunction>::OUTPUT_SIZE);
+  }
+}
@@ -1,53 +1,94 @@
-use super::sha1::{Sha1, Sha1Block, Sha1Digest, SHA1_BLOCK_SIZE};
+use std::{
+  marker::PhantomData,
+  time::{Duration, Instant},
+};
 
-pub struct Sha1HMac {
+use super::hash_function::HashFunction;
+use super::sha1::Sha1;
+use super::sha256::Sha256;
+use crate::utils::aes::utils::constant_time_eq;
+
+// HMAC (RFC 2104) over any hash function
+pub struct HMac<H: HashFunction> {
   key: Vec<u8>,
+  _hash: PhantomData<H>,
 }
 
-impl Sha1HMac {
+pub type Sha1HMac = HMac<Sha1>;
+pub type Sha256HMac = HMac<Sha256>;
+
+impl<H: HashFunction> HMac<H> {
   pub fn new<S: AsRef<[u8]>>(key: &S) -> Self {
     Self {
       key: key.as_ref().to_vec(),
+      _hash: PhantomData,
     }
   }
 
-  pub fn authenticate<S: AsRef<[u8]>>(&self, message: &S) -> Sha1Digest {
-    let k_: Sha1Block = self.get_blocksize_key();
-    let opad: Sha1Block = [0x5c; SHA1_BLOCK_SIZE];
-    let ipad: Sha1Block = [0x36; SHA1_BLOCK_SIZE];
-    let inner_message = [Self::xor_blocks(&k_, &ipad), message.as_ref().to_vec()].concat();
-    let outer_message = [Self::xor_blocks(&k_, &opad), Sha1::hash(&inner_message).to_vec()].concat();
-    Sha1::hash(&outer_message)
+  pub fn authenticate<S: AsRef<[u8]>>(&self, message: &S) -> H::Digest {
+    self.authenticate_chunks([message])
+  }
+
+  // Same as authenticating the concatenation of the chunks, but they are fed one by one to the inner hash
+  pub fn authenticate_chunks<I, T>(&self, chunks: I) -> H::Digest
+  where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+  {
+    let k_ = self.get_blocksize_key();
+    let mut inner_hash = H::new();
+    inner_hash.update(&Self::xor_with(&k_, 0x36));
+    for chunk in chunks {
+      inner_hash.update(chunk.as_ref());
+    }
+    let mut outer_hash = H::new();
+    outer_hash.update(&Self::xor_with(&k_, 0x5c));
+    outer_hash.update(inner_hash.finalize().as_ref());
+    outer_hash.finalize()
   }
 
-  pub fn verify<S: AsRef<[u8]>>(&self, message: &S, expected: Sha1Digest) -> bool {
-    self.authenticate(message) == expected
+  pub fn verify<S: AsRef<[u8]>>(&self, message: &S, expected: H::Digest) -> bool {
+    constant_time_eq(self.authenticate(message).as_ref(), expected.as_ref())
   }
 
-  fn get_blocksize_key(&self) -> Sha1Block {
-    let mut blocksize_key = if self.key.len() > SHA1_BLOCK_SIZE {
-      Sha1::hash(&self.key).to_vec()
+  // Like verify, also returning how long the comparison alone took (the HMAC is computed before starting the clock).
+  // Meant to measure the timing leak of challenges 31 and 32
+  pub fn verify_with_timing<S: AsRef<[u8]>>(&self, message: &S, expected: H::Digest) -> (bool, Duration) {
+    self.verify_with_timing_by(message, expected, &constant_time_eq)
+  }
+
+  // Same as verify_with_timing with another comparison, e.g. an early-exit one to contrast against
+  pub fn verify_with_timing_by<S: AsRef<[u8]>>(
+    &self,
+    message: &S,
+    expected: H::Digest,
+    compare: &dyn Fn(&[u8], &[u8]) -> bool,
+  ) -> (bool, Duration) {
+    let digest = self.authenticate(message);
+    let start = Instant::now();
+    let is_valid = compare(digest.as_ref(), expected.as_ref());
+    (is_valid, start.elapsed())
+  }
+
+  // RFC 2104: keys longer than the block size are replaced by H(key), shorter ones are zero-padded
+  fn get_blocksize_key(&self) -> Vec<u8> {
+    let mut blocksize_key = if self.key.len() > H::BLOCK_SIZE {
+      H::digest(&self.key)
     } else {
       self.key.clone()
     };
-    blocksize_key.resize(64, 0);
-    blocksize_key.try_into().unwrap()
+    blocksize_key.resize(H::BLOCK_SIZE, 0);
+    blocksize_key
   }
 
-  fn xor_blocks(bytes1: &Sha1Block, bytes2: &Sha1Block) -> Vec<u8> {
-    assert_eq!(bytes1.len(), bytes2.len());
-    bytes1
-      .as_ref()
-      .into_iter()
-      .zip(bytes2.as_ref().into_iter())
-      .map(|(a, b)| a ^ b)
-      .collect()
+  fn xor_with(key: &[u8], pad: u8) -> Vec<u8> {
+    key.iter().map(|b| b ^ pad).collect()
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::utils::conversion::hex_string::HexString;
+  use crate::utils::{conversion::hex_string::HexString, mac::sha1::SHA1_BLOCK_SIZE};
 
 use super::*;
 
@@ -75,4 +116,153 @@ use super::*;
       HexString::try_from("4f831c69ba2b801202973dd79b133b39bf6bcd44").unwrap()
     );
   }
+  #[test]
+  fn test_sha1_hmac_key_longer_than_block_size() {
+    let key = [0xaa; 100];
+    let message = b"Test Using Larger Than Block-Size Key - Hash Key First";
+    let hmac = Sha1HMac::new(&key);
+    let digest = hmac.authenticate(message);
+    assert_eq!(
+      HexString::try_from(digest.to_vec()).unwrap(),
+      HexString::try_from("7d83b6c880e2c47de41376b6abe094eda512e2d6").unwrap()
+    );
+    // A key of exactly one block must be used as is, not hashed
+    let hmac = Sha1HMac::new(&[0xaa; SHA1_BLOCK_SIZE]);
+    assert_eq!(
+      HexString::try_from(hmac.authenticate(message).to_vec()).unwrap(),
+      HexString::try_from("070a98992c4c1a83474cb780fc564608df3cf503").unwrap()
+    );
+  }
+
+  // RFC 2202, section 3
+  #[test]
+  fn test_sha1_hmac_rfc2202_vectors() {
+    let key_4: Vec<u8> = (0x01..=0x19).collect();
+    let cases: [(&[u8], &[u8], &str); 7] = [
+      (&[0x0b; 20], b"Hi There", "b617318655057264e28bc0b6fb378c8ef146be00"),
+      (b"Jefe", b"what do ya want for nothing?", "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+      (&[0xaa; 20], &[0xdd; 50], "125d7342b9ac11cd91a39af48aa17b4f63f175d3"),
+      (&key_4, &[0xcd; 50], "4c9007f4026250c6bc8414f9bf50c86c2d7235da"),
+      (&[0x0c; 20], b"Test With Truncation", "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04"),
+      (
+        &[0xaa; 80],
+        b"Test Using Larger Than Block-Size Key - Hash Key First",
+        "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+      ),
+      (
+        &[0xaa; 80],
+        b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data",
+        "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+      ),
+    ];
+    for (key, message, expected) in cases {
+      let hmac = Sha1HMac::new(&key);
+      let digest = hmac.authenticate(&message);
+      assert_eq!(
+        HexString::try_from(digest.to_vec()).unwrap(),
+        HexString::try_from(expected).unwrap()
+      );
+      assert!(hmac.verify(&message, digest));
+    }
+  }
+
+  // RFC 4231, section 4. Test case 5 truncates the output to 128 bits
+  #[test]
+  fn test_sha256_hmac_rfc4231_vectors() {
+    let key_2: Vec<u8> = (0x01..=0x19).collect();
+    let cases: [(&[u8], &[u8], &str); 7] = [
+      (&[0x0b; 20], b"Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+      (
+        b"Jefe",
+        b"what do ya want for nothing?",
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+      ),
+      (&[0xaa; 20], &[0xdd; 50], "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"),
+      (&key_2, &[0xcd; 50], "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b"),
+      (&[0x0c; 20], b"Test With Truncation", "a3b6167473100ee06e0c796c2955552b"),
+      (
+        &[0xaa; 131],
+        b"Test Using Larger Than Block-Size Key - Hash Key First",
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+      ),
+      (
+        &[0xaa; 131],
+        b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+        "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+      ),
+    ];
+    for (key, message, expected) in cases {
+      let hmac = Sha256HMac::new(&key);
+      let digest = hmac.authenticate(&message);
+      let expected = HexString::try_from(expected).unwrap();
+      assert_eq!(HexString::try_from(digest[..expected.len_bytes()].to_vec()).unwrap(), expected);
+      assert!(hmac.verify(&message, digest));
+      assert!(!hmac.verify(b"another message", digest));
+    }
+  }
+
+  #[test]
+  fn test_sha1_hmac_authenticate_chunks() {
+    let hmac = Sha1HMac::new(b"YELLOW SUBMARINE");
+    assert_eq!(hmac.authenticate_chunks(["foo", "bar"]), hmac.authenticate(b"foobar"));
+    let chunks: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 50]).collect();
+    assert_eq!(hmac.authenticate_chunks(&chunks), hmac.authenticate(&chunks.concat()));
+    assert_eq!(hmac.authenticate_chunks(Vec::<&[u8]>::new()), hmac.authenticate(b""));
+  }
+
+  // Minimum over many runs of the comparison time when the first mismatch is at each position
+  fn min_timings(compare: &dyn Fn(&[u8], &[u8]) -> bool) -> Vec<Duration> {
+    let hmac = Sha1HMac::new(b"YELLOW SUBMARINE");
+    let message = b"file=foo";
+    let digest = hmac.authenticate(message);
+    (0..digest.len())
+      .map(|i| {
+        let mut expected = digest;
+        expected[i] ^= 1;
+        (0..2000)
+          .map(|_| {
+            let (is_valid, elapsed) = hmac.verify_with_timing_by(message, expected, compare);
+            assert!(!is_valid);
+            elapsed
+          })
+          .min()
+          .unwrap()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_verify_with_timing_is_flat() {
+    let hmac = Sha1HMac::new(b"YELLOW SUBMARINE");
+    let digest = hmac.authenticate(b"file=foo");
+    assert!(hmac.verify_with_timing(b"file=foo", digest).0);
+    assert!(!hmac.verify_with_timing(b"file=bar", digest).0);
+
+    // A comparison that stops at the first mismatch, slowed down as in challenge 31, leaks the position
+    let insecure = |a: &[u8], b: &[u8]| {
+      for (x, y) in a.iter().zip(b) {
+        if x != y {
+          return false;
+        }
+        std::thread::sleep(Duration::from_micros(50));
+      }
+      true
+    };
+    let mut expected = digest;
+    expected[10] ^= 1;
+    let (_, late_mismatch) = hmac.verify_with_timing_by(b"file=foo", expected, &insecure);
+    expected = digest;
+    expected[0] ^= 1;
+    let (_, early_mismatch) = hmac.verify_with_timing_by(b"file=foo", expected, &insecure);
+    assert!(late_mismatch > early_mismatch + Duration::from_micros(400));
+  }
+
+  // Timing measurements are noisy on loaded machines, run with --ignored
+  #[test]
+  #[ignore]
+  fn test_constant_time_eq_timing_spread() {
+    let timings = min_timings(&constant_time_eq);
+    let spread = *timings.iter().max().unwrap() - *timings.iter().min().unwrap();
+    assert!(spread < Duration::from_micros(5), "{timings:?}");
+  }
 }
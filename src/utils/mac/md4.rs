@@ -0,0 +1,192 @@
+pub const MD4_BLOCK_SIZE: usize = 64;
+pub type MD4Digest = [u8; 16];
+pub type MD4Block = [u8; MD4_BLOCK_SIZE];
+pub const MD4_INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+// Boolean function, additive constant, order in which the words are used and the shifts of one round
+type MD4Round = (fn(u32, u32, u32) -> u32, u32, [usize; 16], [u32; 4]);
+
+// MD4 (RFC 1320). Same Merkle-Damgard structure as SHA-1, but words and the length are little-endian
+#[derive(Clone)]
+pub struct MD4 {
+  states: [u32; 4],
+  buf: Vec<u8>,
+  data_len: u64,
+}
+
+impl MD4 {
+  pub fn new() -> Self {
+    Self::new_with_fixed_state(MD4_INITIAL_STATE, 0)
+  }
+
+  // Starts from a given state as if data_len bytes had already been hashed, for length extension attacks
+  pub fn new_with_fixed_state(states: [u32; 4], data_len: u64) -> Self {
+    Self {
+      states,
+      buf: Vec::new(),
+      data_len,
+    }
+  }
+
+  pub fn update<S: AsRef<[u8]>>(&mut self, data: &S) {
+    self.buf.extend_from_slice(data.as_ref());
+    self.data_len += data.as_ref().len() as u64;
+    while self.buf.len() >= MD4_BLOCK_SIZE {
+      let block: MD4Block = self.buf[..MD4_BLOCK_SIZE].try_into().unwrap();
+      self.buf.drain(..MD4_BLOCK_SIZE);
+      self.states = Self::compress(self.states, &block);
+    }
+  }
+
+  // The padding is processed on a copy of the state, so finalize can be called many times and update can go on
+  pub fn finalize(&self) -> MD4Digest {
+    let padded_buf = [self.buf.clone(), md4_md_padding(self.data_len as usize)].concat();
+    let states = padded_buf
+      .chunks(MD4_BLOCK_SIZE)
+      .fold(self.states, |states, block| Self::compress(states, block.try_into().unwrap()));
+    let mut result: MD4Digest = [0u8; 16];
+    for (chunk, state) in result.chunks_mut(4).zip(states) {
+      chunk.copy_from_slice(&state.to_le_bytes());
+    }
+    result
+  }
+
+  pub fn hash<S: AsRef<[u8]>>(data: &S) -> MD4Digest {
+    let mut hash_fn = Self::new();
+    hash_fn.update(data);
+    hash_fn.finalize()
+  }
+
+  // The MD4 compression function: three rounds of 16 steps, each one with its own boolean function,
+  // order of the words, constant and shifts
+  pub fn compress(states: [u32; 4], block: &MD4Block) -> [u32; 4] {
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+      *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    let rounds: [MD4Round; 3] = [
+      (|x, y, z| (x & y) | (!x & z), 0, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15], [3, 7, 11, 19]),
+      (|x, y, z| (x & y) | (x & z) | (y & z), 0x5a827999, [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15], [3, 5, 9, 13]),
+      (|x, y, z| x ^ y ^ z, 0x6ed9eba1, [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15], [3, 9, 11, 15]),
+    ];
+
+    let mut regs = states;
+    for (function, constant, order, shifts) in rounds {
+      for (i, &k) in order.iter().enumerate() {
+        // The register updated goes a, d, c, b, a, ... and the other three are taken in the order that follows it
+        let target = (4 - i % 4) % 4;
+        let (b, c, d) = (regs[(target + 1) % 4], regs[(target + 2) % 4], regs[(target + 3) % 4]);
+        regs[target] = regs[target]
+          .wrapping_add(function(b, c, d))
+          .wrapping_add(words[k])
+          .wrapping_add(constant)
+          .rotate_left(shifts[i % 4]);
+      }
+    }
+
+    let mut result = states;
+    for (x, y) in result.iter_mut().zip(regs) {
+      *x = x.wrapping_add(y);
+    }
+    result
+  }
+}
+
+impl Default for MD4 {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// Padding MD4 appends to a message of message_len bytes. Like SHA-1's, with the length in bits little-endian
+pub fn md4_md_padding(message_len: usize) -> Vec<u8> {
+  let mut padding = vec![0x80];
+  while (message_len + padding.len()) % MD4_BLOCK_SIZE != 56 {
+    padding.push(0);
+  }
+  padding.extend_from_slice(&((message_len as u64) * 8).to_le_bytes());
+  padding
+}
+
+// Length extension (Challenge 30), the same as sha1_length_extension: returns (glue || extension, forged digest)
+pub fn md4_length_extension(original_mac: MD4Digest, original_len: usize, extension: &[u8]) -> (Vec<u8>, MD4Digest) {
+  let glue = md4_md_padding(original_len);
+  let mut states = [0u32; 4];
+  for (state, bytes) in states.iter_mut().zip(original_mac.chunks(4)) {
+    *state = u32::from_le_bytes(bytes.try_into().unwrap());
+  }
+  let mut md4 = MD4::new_with_fixed_state(states, (original_len + glue.len()) as u64);
+  md4.update(&extension);
+  ([glue, extension.to_vec()].concat(), md4.finalize())
+}
+
+// Secret-prefix MAC, MD4(key || message)
+pub struct MD4MAC {
+  key: Vec<u8>,
+}
+
+impl MD4MAC {
+  pub fn new<S: AsRef<[u8]>>(key: &S) -> Self {
+    Self {
+      key: key.as_ref().to_vec(),
+    }
+  }
+
+  pub fn authenticate<S: AsRef<[u8]>>(&self, message: &S) -> MD4Digest {
+    let mut hash_fn = MD4::new();
+    hash_fn.update(&self.key);
+    hash_fn.update(message);
+    hash_fn.finalize()
+  }
+
+  pub fn verify<S: AsRef<[u8]>>(&self, message: &S, expected: MD4Digest) -> bool {
+    self.authenticate(message) == expected
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::conversion::hex_string::HexString;
+
+  // RFC 1320, appendix A.5
+  #[test]
+  fn test_md4_rfc1320_vectors() {
+    let cases: [(&[u8], &str); 5] = [
+      (b"", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+      (b"abc", "a448017aaf21d8525fc10ae87aa6729d"),
+      (b"message digest", "d9130a8164549fe818874806e1c7014b"),
+      (b"abcdefghijklmnopqrstuvwxyz", "d79e1c308aa5bbcdeea8ed63df412da9"),
+      (
+        b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "e33b4ddc9c38f2199c3e7b164fcc0536",
+      ),
+    ];
+    for (message, expected) in cases {
+      assert_eq!(HexString::from_array(&MD4::hash(&message)), HexString::try_from(expected).unwrap());
+    }
+  }
+
+  #[test]
+  fn test_md4_finalize_is_idempotent() {
+    let mut hash_fn = MD4::new();
+    hash_fn.update(b"abc");
+    assert_eq!(hash_fn.finalize(), hash_fn.finalize());
+    assert_eq!(hash_fn.finalize(), MD4::hash(b"abc"));
+    hash_fn.update(b"def");
+    assert_eq!(hash_fn.finalize(), MD4::hash(b"abcdef"));
+  }
+
+  #[test]
+  fn test_md4_length_extension_forges_mac() {
+    let key = b"YELLOW SUBMARINE";
+    let mac = MD4MAC::new(key);
+    let message = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+    let original_mac = mac.authenticate(message);
+    let (appended, forged) = md4_length_extension(original_mac, key.len() + message.len(), b";admin=true");
+    let forged_message = [message.as_slice(), &appended].concat();
+    assert!(forged_message.ends_with(b";admin=true"));
+    assert!(mac.verify(&forged_message, forged));
+    assert!(!mac.verify(message, forged));
+  }
+}
@@ -1,2 +1,8 @@
 pub mod sha1;
-pub mod hmac;
\ No newline at end of file
+pub mod hmac;
+pub mod cbc_mac;
+pub mod sha1_collision;
+pub mod hash_function;
+pub mod sha256;
+pub mod truncated;
+pub mod md4;
\ No newline at end of file
@@ -1,7 +1,9 @@
 pub const SHA1_BLOCK_SIZE: usize = 64;
 pub type Sha1Digest = [u8; 20];
 pub type Sha1Block = [u8; SHA1_BLOCK_SIZE];
+pub const SHA1_INITIAL_STATE: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
 
+#[derive(Clone)]
 pub struct Sha1 {
   h: [u32; 5],
   buf: Vec<u8>,
@@ -11,7 +13,7 @@ pub struct Sha1 {
 impl Sha1 {
   pub fn new() -> Self {
     Self {
-      h: [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0],
+      h: SHA1_INITIAL_STATE,
       buf: Vec::new(),
       data_len: 0
     }
@@ -48,7 +50,7 @@ impl Sha1 {
   }
 
   pub fn reset(&mut self) {
-    self.h = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    self.h = SHA1_INITIAL_STATE;
     self.buf = Vec::new();
     self.data_len = 0;
   }
@@ -60,6 +62,11 @@ impl Sha1 {
   }
 
   fn process_block(&mut self, block: &Sha1Block) {
+    self.h = Self::compress(self.h, block);
+  }
+
+  // The SHA-1 compression function, with no padding nor length bookkeeping
+  pub fn compress(state: [u32; 5], block: &Sha1Block) -> [u32; 5] {
     let mut words = [0u32; 80];
 
     for i in 0..16 {
@@ -73,8 +80,7 @@ impl Sha1 {
       );
     }
 
-    let (mut a, mut b, mut c, mut d, mut e) =
-      (self.h[0], self.h[1], self.h[2], self.h[3], self.h[4]);
+    let (mut a, mut b, mut c, mut d, mut e) = (state[0], state[1], state[2], state[3], state[4]);
 
     for i in 0..80 {
       let f;
@@ -106,11 +112,13 @@ impl Sha1 {
       a = temp;
     }
 
-    self.h[0] = self.h[0].wrapping_add(a);
-    self.h[1] = self.h[1].wrapping_add(b);
-    self.h[2] = self.h[2].wrapping_add(c);
-    self.h[3] = self.h[3].wrapping_add(d);
-    self.h[4] = self.h[4].wrapping_add(e);
+    [
+      state[0].wrapping_add(a),
+      state[1].wrapping_add(b),
+      state[2].wrapping_add(c),
+      state[3].wrapping_add(d),
+      state[4].wrapping_add(e),
+    ]
   }
 
   fn rotate_left(value: u32, amount: u32) -> u32 {
@@ -147,6 +155,89 @@ impl Sha1Mac {
   pub fn verify<S: AsRef<[u8]>>(&self, message: &S, expected: Sha1Digest) -> bool {
     self.authenticate(message) == expected
   }
+
+  // Processes key || message once, so that the digest can be obtained many times without rehashing
+  pub fn authenticator_for<S: AsRef<[u8]>>(&self, message: &S) -> Sha1MacAuthenticator {
+    let mut state = Sha1::new();
+    state.update(&self.key);
+    state.update(message);
+    Sha1MacAuthenticator { state }
+  }
+
+  // For messages too large to hold in memory, see Sha1MacContext
+  pub fn context(&self) -> Sha1MacContext {
+    Sha1MacContext::new(&self.key)
+  }
+}
+
+// Streaming version of Sha1Mac: the key is fed once, then the message in chunks of any size
+#[derive(Clone)]
+pub struct Sha1MacContext {
+  state: Sha1,
+}
+
+impl Sha1MacContext {
+  pub fn new<S: AsRef<[u8]>>(key: &S) -> Self {
+    let mut state = Sha1::new();
+    state.update(key);
+    Self { state }
+  }
+
+  pub fn update<S: AsRef<[u8]>>(&mut self, chunk: &S) {
+    self.state.update(chunk);
+  }
+
+  pub fn finalize(mut self) -> Sha1Digest {
+    self.state.finalize()
+  }
+}
+
+pub struct Sha1MacAuthenticator {
+  state: Sha1,
+}
+
+impl Sha1MacAuthenticator {
+  pub fn finalize(&self) -> Sha1Digest {
+    self.state.clone().finalize()
+  }
+
+  // Digest of key || message || suffix, reusing the cached state
+  pub fn finalize_with<S: AsRef<[u8]>>(&self, suffix: &S) -> Sha1Digest {
+    let mut state = self.state.clone();
+    state.update(suffix);
+    state.finalize()
+  }
+
+  pub fn verify(&self, expected: Sha1Digest) -> bool {
+    self.finalize() == expected
+  }
+}
+
+// Padding SHA-1 appends to a message of message_len bytes: 0x80, zeros up to 56 (mod 64) bytes and the length in bits
+pub fn sha1_md_padding(message_len: usize) -> Vec<u8> {
+  let mut padding = vec![0x80];
+  while (message_len + padding.len()) % SHA1_BLOCK_SIZE != 56 {
+    padding.push(0);
+  }
+  padding.extend_from_slice(&((message_len as u64) * 8).to_be_bytes());
+  padding
+}
+
+/*
+  Length extension (Challenge 29). original_mac = SHA1(key || message) with original_len = |key || message| is
+  the state of SHA-1 after hashing key || message || glue, glue being its padding. Resuming from that state gives
+  SHA1(key || message || glue || extension) without knowing the key.
+  Returns (glue || extension, forged digest), the first one being what has to be appended to the message
+*/
+pub fn sha1_length_extension(original_mac: Sha1Digest, original_len: usize, extension: &[u8]) -> (Vec<u8>, Sha1Digest) {
+  let glue = sha1_md_padding(original_len);
+  let mut h = [0u32; 5];
+  for (word, bytes) in h.iter_mut().zip(original_mac.chunks(4)) {
+    *word = u32::from_be_bytes(bytes.try_into().unwrap());
+  }
+  let mut sha1 = Sha1::new_with_fixed_state(h, (original_len + glue.len()) as u64);
+  sha1.update(&extension);
+  ([glue, extension.to_vec()].concat(), sha1.finalize())
 }
 
 #[cfg(test)]
@@ -184,6 +275,20 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_sha1_mac_context_matches_authenticate() {
+    let mac = Sha1Mac::new(b"YELLOW SUBMARINE");
+    let message: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    for chunk_size in [1, 7, 64, 1000] {
+      let mut context = mac.context();
+      for chunk in message.chunks(chunk_size) {
+        context.update(&chunk);
+      }
+      assert_eq!(context.finalize(), mac.authenticate(&message));
+    }
+    assert_eq!(Sha1MacContext::new(b"YELLOW SUBMARINE").finalize(), mac.authenticate(b""));
+  }
+
   #[test]
   fn test_multiple_data() {
     let mut hash_fn = Sha1::new();
@@ -210,6 +315,13 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_sha1_digest_from_array() {
+    let digest = hash(b"abc");
+    assert_eq!(HexString::from_array(&digest), HexString::try_from("A9993E364706816ABA3E25717850C26C9CD0D89D").unwrap());
+    assert_eq!(HexString::from_array(&digest), HexString::try_from(digest.to_vec()).unwrap());
+  }
+
   #[test]
   fn test_sha1_mac() {
     let secret_key = b"YELLOW SUBMARINE";
@@ -229,4 +341,34 @@ mod tests {
     assert!(mac.verify(b"HOLA", digest));
     assert!(!mac.verify(b"", digest));
   }
+
+  #[test]
+  fn test_sha1_length_extension_forges_mac() {
+    let key = b"YELLOW SUBMARINE";
+    let mac = Sha1Mac::new(key);
+    let extension = b";admin=true";
+    // The glue fits in the last block for the first message and needs an extra block for the others
+    for message in [&b"comment1=cooking%20MCs;userdata=foo"[..], &[b'A'; 40], &[b'A'; 48]] {
+      let original_mac = mac.authenticate(&message);
+      let (appended, forged) = sha1_length_extension(original_mac, key.len() + message.len(), extension);
+      assert!(appended.ends_with(extension));
+      let forged_message = [message, &appended].concat();
+      assert!((key.len() + forged_message.len() - extension.len()).is_multiple_of(SHA1_BLOCK_SIZE));
+      assert!(mac.verify(&forged_message, forged));
+    }
+  }
+
+  #[test]
+  fn test_sha1_mac_authenticator() {
+    let mac = Sha1Mac::new(b"YELLOW SUBMARINE");
+    let message = b"AGUANTE EL CLUB ATLETICO Y RECREATIVO GENERAL SAN MARTIN DE LAS ESCOBAS";
+    let authenticator = mac.authenticator_for(message);
+    assert_eq!(authenticator.finalize(), mac.authenticate(message));
+    assert_eq!(authenticator.finalize(), authenticator.finalize());
+    assert!(authenticator.verify(mac.authenticate(message)));
+    assert_eq!(
+      authenticator.finalize_with(b";admin=true"),
+      mac.authenticate(&[message.as_ref(), b";admin=true"].concat())
+    );
+  }
 }
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use rand::{thread_rng, Rng};
+
+use super::sha1::{Sha1, Sha1Block, SHA1_BLOCK_SIZE, SHA1_INITIAL_STATE};
+
+// Only a birthday search over a truncated state is feasible, so the truncation is bounded
+const MAX_TRUNCATED_BITS: u32 = 48;
+
+// Internal state of SHA-1 after compressing every full block of msg, without padding
+pub fn state_after_blocks<S: AsRef<[u8]>>(msg: &S) -> [u32; 5] {
+  msg
+    .as_ref()
+    .chunks_exact(SHA1_BLOCK_SIZE)
+    .fold(SHA1_INITIAL_STATE, |state, block| {
+      Sha1::compress(state, block.try_into().unwrap())
+    })
+}
+
+// The first `bits` bits of the state (h0 || h1 || ...)
+pub fn truncate_state(state: &[u32; 5], bits: u32) -> u64 {
+  let top = ((state[0] as u64) << 32) | state[1] as u64;
+  top >> (64 - bits)
+}
+
+// Birthday search for two different messages of `blocks` blocks whose SHA-1 states agree on their first `bits` bits.
+// Both messages share a random prefix of blocks - 1 blocks, so only the last compression is computed per attempt.
+// It takes around 2^(bits/2) compressions, which is why real SHA-1 cannot be attacked like this
+pub fn find_truncated_state_collision(blocks: usize, bits: u32) -> (Vec<u8>, Vec<u8>) {
+  assert!(blocks > 0, "At least one block is needed");
+  assert!(bits > 0 && bits <= MAX_TRUNCATED_BITS, "The truncation must be between 1 and {MAX_TRUNCATED_BITS} bits");
+  let mut rng = thread_rng();
+  let prefix: Vec<u8> = (0..(blocks - 1) * SHA1_BLOCK_SIZE).map(|_| rng.gen()).collect();
+  let prefix_state = state_after_blocks(&prefix);
+  let mut seen: HashMap<u64, Sha1Block> = HashMap::new();
+  loop {
+    let mut last_block: Sha1Block = [0; SHA1_BLOCK_SIZE];
+    rng.fill(&mut last_block[..]);
+    let truncated = truncate_state(&Sha1::compress(prefix_state, &last_block), bits);
+    match seen.get(&truncated) {
+      Some(other_block) if *other_block != last_block => {
+        return (
+          [prefix.as_slice(), other_block].concat(),
+          [prefix.as_slice(), &last_block].concat(),
+        );
+      }
+      _ => {
+        seen.insert(truncated, last_block);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_state_after_blocks_matches_sha1() {
+    // The padding of a 55 byte message fits in the same block
+    let msg = [b'A'; 55];
+    let mut padded = msg.to_vec();
+    padded.push(0x80);
+    padded.extend_from_slice(&(55u64 * 8).to_be_bytes());
+    let state = state_after_blocks(&padded);
+    let digest: Vec<u8> = state.iter().flat_map(|h| h.to_be_bytes()).collect();
+    assert_eq!(digest, Sha1::hash(&msg));
+  }
+
+  #[test]
+  fn test_find_truncated_state_collision() {
+    let bits = 24;
+    let (msg1, msg2) = find_truncated_state_collision(2, bits);
+    assert_ne!(msg1, msg2);
+    assert_eq!(msg1.len(), 2 * SHA1_BLOCK_SIZE);
+    assert_eq!(msg1[..SHA1_BLOCK_SIZE], msg2[..SHA1_BLOCK_SIZE]);
+    let (state1, state2) = (state_after_blocks(&msg1), state_after_blocks(&msg2));
+    assert_eq!(truncate_state(&state1, bits), truncate_state(&state2, bits));
+    assert_ne!(state1, state2);
+  }
+}
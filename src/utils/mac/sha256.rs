@@ -0,0 +1,184 @@
+pub const SHA256_BLOCK_SIZE: usize = 64;
+pub type Sha256Digest = [u8; 32];
+pub type Sha256Block = [u8; SHA256_BLOCK_SIZE];
+pub const SHA256_INITIAL_STATE: [u32; 8] = [
+  0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// First 32 bits of the fractional parts of the cube roots of the first 64 primes
+const ROUND_CONSTANTS: [u32; 64] = [
+  0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+  0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+  0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+  0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+  0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+  0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+  0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+  0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[derive(Clone)]
+pub struct Sha256 {
+  h: [u32; 8],
+  buf: Vec<u8>,
+  data_len: u64,
+}
+
+impl Sha256 {
+  pub fn new() -> Self {
+    Self {
+      h: SHA256_INITIAL_STATE,
+      buf: Vec::new(),
+      data_len: 0,
+    }
+  }
+
+  // Starts from a given state as if data_len bytes had already been hashed, for length extension attacks
+  pub fn new_with_fixed_state(h: [u32; 8], data_len: u64) -> Self {
+    Self {
+      h,
+      buf: Vec::new(),
+      data_len,
+    }
+  }
+
+  pub fn update<S: AsRef<[u8]>>(&mut self, data: &S) {
+    self.buf.extend_from_slice(data.as_ref());
+    self.data_len += data.as_ref().len() as u64;
+    while self.buf.len() >= SHA256_BLOCK_SIZE {
+      let block: Sha256Block = self.buf[..SHA256_BLOCK_SIZE].try_into().unwrap();
+      self.buf.drain(..SHA256_BLOCK_SIZE);
+      self.h = Self::compress(self.h, &block);
+    }
+  }
+
+  // Same padding as SHA-1: 0x80, zeros up to 56 (mod 64) bytes and the length in bits as a big-endian u64
+  pub fn finalize(&mut self) -> Sha256Digest {
+    let mut padded_buf = std::mem::take(&mut self.buf);
+    padded_buf.push(0x80);
+    while padded_buf.len() % SHA256_BLOCK_SIZE != 56 {
+      padded_buf.push(0);
+    }
+    padded_buf.extend_from_slice(&(self.data_len * 8).to_be_bytes());
+
+    for block in padded_buf.chunks(SHA256_BLOCK_SIZE) {
+      self.h = Self::compress(self.h, block.try_into().unwrap());
+    }
+
+    let mut result: Sha256Digest = [0u8; 32];
+    for (chunk, h) in result.chunks_mut(4).zip(self.h) {
+      chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    result
+  }
+
+  pub fn reset(&mut self) {
+    self.h = SHA256_INITIAL_STATE;
+    self.buf = Vec::new();
+    self.data_len = 0;
+  }
+
+  pub fn hash<S: AsRef<[u8]>>(data: &S) -> Sha256Digest {
+    let mut hash_fn = Self::new();
+    hash_fn.update(data);
+    hash_fn.finalize()
+  }
+
+  // The SHA-256 compression function (FIPS 180-4, 6.2.2), with no padding nor length bookkeeping
+  pub fn compress(state: [u32; 8], block: &Sha256Block) -> [u32; 8] {
+    let mut words = [0u32; 64];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+      *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..64 {
+      let s0 = words[i - 15].rotate_right(7) ^ words[i - 15].rotate_right(18) ^ (words[i - 15] >> 3);
+      let s1 = words[i - 2].rotate_right(17) ^ words[i - 2].rotate_right(19) ^ (words[i - 2] >> 10);
+      words[i] = words[i - 16].wrapping_add(s0).wrapping_add(words[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for (word, k) in words.iter().zip(ROUND_CONSTANTS) {
+      let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+      let ch = (e & f) ^ (!e & g);
+      let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(k).wrapping_add(*word);
+      let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+      let maj = (a & b) ^ (a & c) ^ (b & c);
+      let temp2 = s0.wrapping_add(maj);
+      h = g;
+      g = f;
+      f = e;
+      e = d.wrapping_add(temp1);
+      d = c;
+      c = b;
+      b = a;
+      a = temp1.wrapping_add(temp2);
+    }
+
+    let mut result = state;
+    for (x, y) in result.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+      *x = x.wrapping_add(y);
+    }
+    result
+  }
+}
+
+impl Default for Sha256 {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::conversion::hex_string::HexString;
+
+  #[test]
+  fn test_sha256_nist_vectors() {
+    assert_eq!(
+      HexString::from_array(&Sha256::hash(b"abc")),
+      HexString::try_from("BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD").unwrap()
+    );
+    assert_eq!(
+      HexString::from_array(&Sha256::hash(b"")),
+      HexString::try_from("E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855").unwrap()
+    );
+    // Two blocks after padding
+    assert_eq!(
+      HexString::from_array(&Sha256::hash(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+      HexString::try_from("248D6A61D20638B8E5C026930C3E6039A33CE45964FF2167F6ECEDD419DB06C1").unwrap()
+    );
+  }
+
+  #[test]
+  fn test_sha256_matches_sha2_crate() {
+    use sha2::Digest;
+    let data: Vec<u8> = (0..=255u8).collect();
+    for len in [1, 55, 56, 63, 64, 65, 119, 128, 256] {
+      assert_eq!(Sha256::hash(&&data[..len]).to_vec(), sha2::Sha256::digest(&data[..len]).to_vec(), "{len}");
+    }
+  }
+
+  #[test]
+  fn test_sha256_multiple_updates() {
+    let data: Vec<u8> = (0..200u8).collect();
+    let mut hash_fn = Sha256::new();
+    for chunk in data.chunks(33) {
+      hash_fn.update(&chunk);
+    }
+    assert_eq!(hash_fn.finalize(), Sha256::hash(&data));
+    hash_fn.reset();
+    hash_fn.update(b"abc");
+    assert_eq!(hash_fn.finalize(), Sha256::hash(b"abc"));
+  }
+
+  #[test]
+  fn test_sha256_new_with_fixed_state() {
+    // After one full block, hashing the rest from the intermediate state gives the same digest
+    let data = [b'A'; 100];
+    let state = Sha256::compress(SHA256_INITIAL_STATE, &data[..SHA256_BLOCK_SIZE].try_into().unwrap());
+    let mut hash_fn = Sha256::new_with_fixed_state(state, SHA256_BLOCK_SIZE as u64);
+    hash_fn.update(&&data[SHA256_BLOCK_SIZE..]);
+    assert_eq!(hash_fn.finalize(), Sha256::hash(&data));
+  }
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use super::hash_function::HashFunction;
+
+// Any hash truncated to its first len bytes, to build weak hashes whose collisions can actually be found
+pub struct Truncated<H: HashFunction> {
+  len: usize,
+  _hash: PhantomData<H>,
+}
+
+impl<H: HashFunction> Truncated<H> {
+  pub fn new(len: usize) -> Self {
+    assert!(len > 0 && len <= H::OUTPUT_SIZE, "The length must be between 1 and {} bytes", H::OUTPUT_SIZE);
+    Self { len, _hash: PhantomData }
+  }
+
+  pub fn digest<S: AsRef<[u8]>>(&self, data: &S) -> Vec<u8> {
+    H::digest(data.as_ref())[..self.len].to_vec()
+  }
+
+  // Compression function of the Merkle-Damgard construction: the next state is the digest of state || block
+  pub fn compress(&self, state: &[u8], block: &[u8]) -> Vec<u8> {
+    self.digest(&[state, block].concat())
+  }
+
+  // Merkle-Damgard iteration over blocks of H::BLOCK_SIZE bytes, the last one padded with zeros.
+  // There is no length padding, as in the hashes of challenges 52 to 54
+  pub fn merkle_damgard<S: AsRef<[u8]>>(&self, initial_state: &[u8], message: &S) -> Vec<u8> {
+    message.as_ref().chunks(H::BLOCK_SIZE).fold(initial_state.to_vec(), |state, chunk| {
+      let mut block = chunk.to_vec();
+      block.resize(H::BLOCK_SIZE, 0);
+      self.compress(&state, &block)
+    })
+  }
+
+  // Hashes the messages in order until two of them collide, returning their indices
+  pub fn find_collision<I, S>(&self, messages: I) -> Option<(usize, usize)>
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<[u8]>,
+  {
+    let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+    for (i, message) in messages.into_iter().enumerate() {
+      if let Some(j) = seen.insert(self.digest(&message), i) {
+        return Some((j, i));
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::mac::{sha1::Sha1, sha256::Sha256};
+
+  #[test]
+  fn test_truncated_digest() {
+    let hash = Truncated::<Sha1>::new(2);
+    assert_eq!(hash.digest(b"abc"), hash.digest(b"abc"));
+    assert_eq!(hash.digest(b"abc"), Sha1::hash(b"abc")[..2]);
+    assert_eq!(Truncated::<Sha256>::new(3).digest(b"abc"), Sha256::hash(b"abc")[..3]);
+  }
+
+  #[test]
+  fn test_truncated_collisions_follow_birthday_bound() {
+    // With 16 bits, the first collision is expected after about 2^8 messages
+    let hash = Truncated::<Sha1>::new(2);
+    let (i, j) = hash.find_collision((0..1u32 << 12).map(|i| i.to_be_bytes())).unwrap();
+    assert!(j > 1 << 4 && j < 1 << 12, "{j}");
+    assert_eq!(hash.digest(&(i as u32).to_be_bytes()), hash.digest(&(j as u32).to_be_bytes()));
+  }
+
+  #[test]
+  fn test_truncated_merkle_damgard() {
+    let hash = Truncated::<Sha1>::new(2);
+    let message: Vec<u8> = (0..100).collect();
+    let first = hash.compress(&[0, 0], &message[..64]);
+    let mut last_block = message[64..].to_vec();
+    last_block.resize(64, 0);
+    assert_eq!(hash.merkle_damgard(&[0, 0], &message), hash.compress(&first, &last_block));
+    assert_eq!(hash.merkle_damgard(&[1, 2], b""), vec![1, 2]);
+  }
+}
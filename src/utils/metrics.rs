@@ -1,4 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+  cmp::Reverse,
+  collections::{HashMap, HashSet},
+};
+
+use num::integer::gcd;
 
 use super::conversion::conversion::ConversionError;
 
@@ -64,6 +69,50 @@ pub fn smallest_feasible_keysizes<S: AsRef<[u8]>>(
   result.into_iter().take(amount).collect()
 }
 
+// Kasiski examination: the same plaintext under the same part of a repeating key gives the same ciphertext,
+// so the distances between repeated sequences tend to be multiples of the keysize.
+// Returns the distance between consecutive occurrences of every repeated sequence of sequence_len bytes,
+// and no spacings for empty sequences
+pub fn kasiski_spacings<S: AsRef<[u8]>>(encrypted: S, sequence_len: usize) -> Vec<usize> {
+  if sequence_len == 0 {
+    return vec![];
+  }
+  let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+  let mut spacings: Vec<usize> = vec![];
+  for (position, sequence) in encrypted.as_ref().windows(sequence_len).enumerate() {
+    if let Some(previous) = last_seen.insert(sequence, position) {
+      spacings.push(position - previous);
+    }
+  }
+  spacings
+}
+
+// GCD of all the spacings, None if there are no repeated sequences.
+// A single accidental repetition can bring it down to 1, so longer sequences give more reliable results
+pub fn kasiski_keysize<S: AsRef<[u8]>>(encrypted: S, sequence_len: usize) -> Option<usize> {
+  kasiski_spacings(encrypted, sequence_len).into_iter().reduce(gcd)
+}
+
+// Keysizes sorted by how many spacings they divide, to cross-reference with smallest_feasible_keysizes.
+// A keysize of 0 is never a candidate
+pub fn kasiski_keysize_candidates<S: AsRef<[u8]>>(
+  encrypted: S,
+  sequence_len: usize,
+  min_threshold: u8,
+  max_threshold: u8,
+  amount: usize,
+) -> Vec<(u8, usize)> {
+  let spacings = kasiski_spacings(encrypted, sequence_len);
+  let mut result: Vec<(u8, usize)> = (min_threshold.max(1)..=max_threshold)
+    .map(|keysize| {
+      let divided = spacings.iter().filter(|&&spacing| spacing % keysize as usize == 0).count();
+      (keysize, divided)
+    })
+    .collect();
+  result.sort_by_key(|&(_, divided)| Reverse(divided));
+  result.into_iter().take(amount).collect()
+}
+
 pub fn group_bytes_by_position<S: AsRef<[u8]>>(input: S, keysize: usize) -> Vec<Vec<u8>> {
   let bytes = input.as_ref();
   let num_blocks = bytes.len() / keysize;
@@ -102,6 +151,33 @@ pub fn xor_against_all_bytes_and_find_best<S: AsRef<[u8]>>(bytes: S) -> (u8, f64
   (best_byte, best_fraction)
 }
 
+// Among many candidates, the one that looks most like English once XORed against a single byte (Challenge 4).
+// Returns its index, the key byte and the decrypted line. Pre: candidates is not empty
+pub fn find_xored_line(candidates: &[Vec<u8>]) -> (usize, u8, Vec<u8>) {
+  let (index, (key, _)) = candidates
+    .iter()
+    .map(xor_against_all_bytes_and_find_best)
+    .enumerate()
+    .max_by(|(_, (_, score1)), (_, (_, score2))| score1.total_cmp(score2))
+    .expect("There are no candidates");
+  let plaintext = candidates[index].iter().map(|b| b ^ key).collect();
+  (index, key, plaintext)
+}
+
+// Maps every distinct block to an index (in order of appearance), so repeated blocks in ECB are easy to spot
+pub fn print_block_structure<S: AsRef<[u8]>>(ciphertext: S, block_size: usize) -> String {
+  let mut seen_blocks: HashMap<&[u8], usize> = HashMap::new();
+  ciphertext
+    .as_ref()
+    .chunks(block_size)
+    .map(|block| {
+      let next_symbol = seen_blocks.len();
+      seen_blocks.entry(block).or_insert(next_symbol).to_string()
+    })
+    .collect::<Vec<String>>()
+    .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -173,6 +249,22 @@ mod tests {
     assert!(result[0].1 - (800 as f64) / (29 as f64) <= 1e-6);
   }
 
+  #[test]
+  fn kasiski_examination_of_challenge6() {
+    let base64_contents = fs::read_to_string("src/data/1-6.txt").expect("Failed to read the file");
+    let contents = base64_to_bytes_vector(&base64_contents).expect("Failed to convert from base64");
+    let spacings = kasiski_spacings(&contents, 3);
+    assert!(!spacings.is_empty());
+    // Short sequences repeat by chance too, but most spacings are still multiples of 29
+    assert!(spacings.iter().filter(|&&spacing| spacing % 29 == 0).count() * 2 > spacings.len());
+    assert_eq!(kasiski_keysize_candidates(&contents, 3, 2, 40, 3)[0].0, 29);
+    assert_eq!(kasiski_keysize(&contents, 6), Some(29));
+    assert_eq!(kasiski_keysize(b"no repetitions here", 3), None);
+    assert!(kasiski_spacings(&contents, 0).is_empty());
+    assert_eq!(kasiski_keysize_candidates(&contents, 3, 0, 40, 3), kasiski_keysize_candidates(&contents, 3, 1, 40, 3));
+    assert!(kasiski_keysize_candidates(&contents, 3, 0, 0, 3).is_empty());
+  }
+
   #[test]
   fn group_bytes_by_position_test() {
     let str = "Aguante el Club Atletico y Recreativo General San Martin de las Escobas";
@@ -184,4 +276,25 @@ mod tests {
       .unwrap();
     assert_eq!(grouped[0], correct_answer);
   }
+
+  #[test]
+  fn test_find_xored_line() {
+    let contents = fs::read_to_string("src/data/1-4.txt").expect("Failed to read the file");
+    let candidates: Vec<Vec<u8>> = contents
+      .lines()
+      .map(|line| HexString::try_from(line).unwrap().as_vector_of_bytes().unwrap())
+      .collect();
+    let (index, key, plaintext) = find_xored_line(&candidates);
+    assert_eq!(index, 170);
+    assert_eq!(key, 0x35);
+    assert_eq!(plaintext, b"Now that the party is jumping\n");
+  }
+
+  #[test]
+  fn test_print_block_structure() {
+    let (a, b, c) = ([0x41u8; 16], [0x42u8; 16], [0x43u8; 16]);
+    let ciphertext = [a, b, a, c].concat();
+    assert_eq!(print_block_structure(&ciphertext, 16), "0 1 0 2");
+    assert_eq!(print_block_structure(&ciphertext, 32), "0 1");
+  }
 }
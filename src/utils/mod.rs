@@ -8,4 +8,6 @@ pub mod dh;
 pub mod srp;
 pub mod rsa;
 pub mod padding;
-pub mod dsa;
\ No newline at end of file
+pub mod dsa;
+pub mod srp_oda;
+pub mod oracle;
\ No newline at end of file
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+// Profiling of encryption oracles, given as closures from our input to the ciphertext
+
+// Block size of an oracle that pads to whole blocks: the ciphertext grows by exactly one block
+// when the input crosses a block boundary. 0 if it never grows within 256 bytes of input
+pub fn detect_block_size(oracle: &impl Fn(&[u8]) -> Vec<u8>) -> usize {
+  let initial_len = oracle(&[]).len();
+  (1..=256)
+    .find_map(|input_len| {
+      let len = oracle(&vec![b'A'; input_len]).len();
+      (len > initial_len).then_some(len - initial_len)
+    })
+    .unwrap_or(0)
+}
+
+// Three blocks of equal input always contain two aligned ones, whatever the length of the prefix
+pub fn is_ecb(oracle: &impl Fn(&[u8]) -> Vec<u8>, block_size: usize) -> bool {
+  let ciphertext = oracle(&vec![b'A'; 3 * block_size]);
+  let mut seen_blocks: HashSet<&[u8]> = HashSet::new();
+  ciphertext.chunks(block_size).any(|block| !seen_blocks.insert(block))
+}
+
+// Length of the unknown prefix an ECB oracle puts before our input (Challenge 14).
+// The filler completes the last block of the prefix once two equal blocks show up in the ciphertext.
+// If the prefix ends with bytes equal to the filler they count as filler too, and the length found is smaller
+// than the real one. The prefix cannot end with two different bytes, so the larger answer of two fillers is right.
+// Check the oracle with is_ecb first: without ECB the blocks never repeat, and 0 is returned
+pub fn detect_prefix_len(oracle: &impl Fn(&[u8]) -> Vec<u8>, block_size: usize) -> usize {
+  let detect_with = |filler: u8| {
+    (0..block_size).find_map(|filler_len| {
+      let ciphertext = oracle(&vec![filler; filler_len + 2 * block_size]);
+      let blocks: Vec<&[u8]> = ciphertext.chunks(block_size).collect();
+      (1..blocks.len())
+        .find(|&i| blocks[i - 1] == blocks[i])
+        .map(|i| (i - 1) * block_size - filler_len)
+    })
+  };
+  detect_with(b'A').max(detect_with(b'B')).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::aes::{aes::AES, constants::AES_BLOCK_SIZE, utils::AESMode};
+  use rand::{rngs::StdRng, Rng, SeedableRng};
+
+  const SUFFIX: &[u8] = b"Rollin' in my 5.0\nWith my rag-top down\n";
+
+  #[test]
+  fn test_profile_synthetic_ecb_oracle() {
+    let key: [u8; 16] = StdRng::seed_from_u64(7).gen();
+    let prefix = b"a prefix of 21 bytes!";
+    let oracle = |input: &[u8]| AES::encode(&[prefix, input, SUFFIX].concat(), &key, AESMode::ECB).unwrap();
+    assert_eq!(detect_block_size(&oracle), AES_BLOCK_SIZE);
+    assert!(is_ecb(&oracle, AES_BLOCK_SIZE));
+    assert_eq!(detect_prefix_len(&oracle, AES_BLOCK_SIZE), prefix.len());
+
+    let cbc_oracle = |input: &[u8]| AES::encode(&[prefix, input].concat(), &key, AESMode::CBC([0; 16])).unwrap();
+    assert_eq!(detect_block_size(&cbc_oracle), AES_BLOCK_SIZE);
+    assert!(!is_ecb(&cbc_oracle, AES_BLOCK_SIZE));
+    // A stream mode never pads, so the length grows with every byte
+    let ctr_oracle = |input: &[u8]| AES::encode(&input.to_vec(), &key, AESMode::CTR(0)).unwrap();
+    assert_eq!(detect_block_size(&ctr_oracle), 1);
+    assert_eq!(detect_block_size(&|_: &[u8]| vec![0; 16]), 0);
+  }
+
+  #[test]
+  fn test_detect_prefix_len_ending_with_filler() {
+    let key: [u8; 16] = StdRng::seed_from_u64(15).gen();
+    for prefix in [&b"0123456789abcdefA"[..], b"0123456789abcdeB", b"0123456789abcdefAAAAAAAAAAAAAAAAA"] {
+      let oracle = |input: &[u8]| AES::encode(&[prefix, input, SUFFIX].concat(), &key, AESMode::ECB).unwrap();
+      assert_eq!(detect_prefix_len(&oracle, AES_BLOCK_SIZE), prefix.len());
+    }
+  }
+
+  #[test]
+  fn test_detect_prefix_len_of_cbc() {
+    let oracle = |input: &[u8]| AES::encode(&input, b"YELLOW SUBMARINE", AESMode::CBC([0; 16])).unwrap();
+    assert_eq!(detect_prefix_len(&oracle, AES_BLOCK_SIZE), 0);
+  }
+}
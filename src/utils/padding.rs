@@ -1,3 +1,11 @@
+use core::fmt;
+use rand::{thread_rng, Rng};
+
+use super::{
+  aes::utils::constant_time_eq,
+  mac::sha1::{Sha1, Sha1Digest},
+};
+
 pub fn pkcs1_pad(bytes: &[u8], n_size: usize) -> Vec<u8> {
   let padding_len = n_size - 3 - bytes.len();
   [
@@ -19,3 +27,237 @@ pub fn pkcs1_unpad(padded_bytes: &[u8]) -> Vec<u8> {
   }
   padded_bytes[padding_end + 1..].to_vec()
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OAEPError {
+  MessageTooLong(usize, usize),
+  ModulusTooSmall(usize),
+  DecodingError,
+}
+
+impl fmt::Display for OAEPError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::MessageTooLong(len, max_len) => {
+        write!(f, "The message has {len} bytes, at most {max_len} fit in the modulus.")
+      }
+      Self::ModulusTooSmall(k) => {
+        write!(f, "A modulus of {k} bytes is too small for OAEP, it needs at least {} bytes.", 2 * OAEP_HASH_LEN + 2)
+      }
+      Self::DecodingError => write!(f, "OAEP decoding error."),
+    }
+  }
+}
+
+pub const OAEP_HASH_LEN: usize = 20;
+
+// EME-OAEP encoding (RFC 8017, 7.1.1) with SHA-1 and MGF1-SHA1 for a modulus of k bytes:
+// 0x00 || (seed ^ mask) || (lHash || PS || 0x01 || msg) ^ mask, with a random seed
+pub fn oaep_pad(msg: &[u8], k: usize, label: &[u8]) -> Result<Vec<u8>, OAEPError> {
+  let max_len = k.checked_sub(2 * OAEP_HASH_LEN + 2).ok_or(OAEPError::ModulusTooSmall(k))?;
+  if msg.len() > max_len {
+    return Err(OAEPError::MessageTooLong(msg.len(), max_len));
+  }
+  let l_hash = Sha1::hash(&label);
+  let ps = vec![0u8; max_len - msg.len()];
+  let db = [l_hash.as_slice(), &ps, &[0x01], msg].concat();
+  let seed: [u8; OAEP_HASH_LEN] = thread_rng().gen();
+  let masked_db: Vec<u8> = db.iter().zip(mgf1(&seed, db.len(), &sha1_vec)).map(|(a, b)| a ^ b).collect();
+  let masked_seed: Vec<u8> = seed.iter().zip(mgf1(&masked_db, OAEP_HASH_LEN, &sha1_vec)).map(|(a, b)| a ^ b).collect();
+  Ok([[0x00].as_slice(), &masked_seed, &masked_db].concat())
+}
+
+// EME-OAEP decoding (RFC 8017, 7.1.2). Every check is done before deciding, and all failures give the same error,
+// so neither the result nor the timing tells whether it was the first byte, the label hash or the 0x01 separator
+pub fn oaep_unpad(em: &[u8], k: usize, label: &[u8]) -> Result<Vec<u8>, OAEPError> {
+  if em.len() != k || k < 2 * OAEP_HASH_LEN + 2 {
+    return Err(OAEPError::DecodingError);
+  }
+  let (y, masked_seed, masked_db) = (em[0], &em[1..=OAEP_HASH_LEN], &em[OAEP_HASH_LEN + 1..]);
+  let seed: Vec<u8> = masked_seed.iter().zip(mgf1(masked_db, OAEP_HASH_LEN, &sha1_vec)).map(|(a, b)| a ^ b).collect();
+  let db: Vec<u8> = masked_db.iter().zip(mgf1(&seed, masked_db.len(), &sha1_vec)).map(|(a, b)| a ^ b).collect();
+
+  let mut bad = (y != 0) | !constant_time_eq(&db[..OAEP_HASH_LEN], &Sha1::hash(&label));
+  // Position of the first 0x01 after PS, scanning the whole DB whatever is found
+  let (mut found, mut separator) = (false, 0);
+  for (i, &b) in db.iter().enumerate().skip(OAEP_HASH_LEN) {
+    let is_first_one = !found & (b == 0x01);
+    separator |= i * is_first_one as usize;
+    bad |= !found & (b != 0x00) & (b != 0x01);
+    found |= b == 0x01;
+  }
+  if bad | !found {
+    return Err(OAEPError::DecodingError);
+  }
+  Ok(db[separator + 1..].to_vec())
+}
+
+// Zero padding up to a multiple of block_size, adding nothing to aligned inputs.
+// It is ambiguous: "A\0" and "A" pad to the same bytes, so unpadding needs the original length
+pub fn zero_pad(bytes: &[u8], block_size: usize) -> Vec<u8> {
+  let mut padded = bytes.to_vec();
+  padded.resize(bytes.len().next_multiple_of(block_size), 0x00);
+  padded
+}
+
+// None if original_len is longer than the padded bytes or some byte after it is not zero
+pub fn zero_unpad(padded_bytes: &[u8], original_len: usize) -> Option<Vec<u8>> {
+  let (data, padding) = padded_bytes.split_at_checked(original_len)?;
+  padding.iter().all(|&b| b == 0x00).then(|| data.to_vec())
+}
+
+// DER encoding of the DigestInfo of SHA-1 up to the digest (RFC 8017, 9.2, note 1)
+pub const SHA1_DIGEST_INFO_PREFIX: [u8; 15] =
+  [0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14];
+
+// EMSA-PKCS1-v1_5 encoding (RFC 8017, 9.2) with SHA-1: 00 01 FF..FF 00 DigestInfo, em_len bytes long.
+// Returns None if em_len leaves less than 8 bytes of padding
+pub fn emsa_pkcs1_v15_encode(digest: &Sha1Digest, em_len: usize) -> Option<Vec<u8>> {
+  let t = [SHA1_DIGEST_INFO_PREFIX.as_slice(), digest].concat();
+  let ps_len = em_len.checked_sub(t.len() + 3).filter(|&len| len >= 8)?;
+  Some([[0x00, 0x01].as_slice(), &vec![0xff; ps_len], &[0x00], &t].concat())
+}
+
+// Strict parsing of an EMSA-PKCS1-v1_5 block: 00 01, at least 8 bytes of 0xFF, 00 and the DigestInfo of the digest,
+// ending exactly there. Unlike the verifier of Challenge 42, nothing can be hidden after the digest
+pub fn emsa_pkcs1_v15_verify(digest: &Sha1Digest, em: &[u8]) -> bool {
+  if em.len() < 2 || em[0] != 0x00 || em[1] != 0x01 {
+    return false;
+  }
+  let ps_len = em[2..].iter().take_while(|&&b| b == 0xff).count();
+  let rest = &em[2 + ps_len..];
+  let t = [SHA1_DIGEST_INFO_PREFIX.as_slice(), digest].concat();
+  ps_len >= 8 && rest.first() == Some(&0x00) && rest[1..] == t
+}
+
+// MGF1 (RFC 8017, B.2.1): hash(seed || counter) for counter = 0, 1, ... (as 4 big-endian bytes), truncated to length
+pub fn mgf1(seed: &[u8], length: usize, hash: &dyn Fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
+  let mut mask = Vec::with_capacity(length);
+  let mut counter = 0u32;
+  while mask.len() < length {
+    mask.extend_from_slice(&hash(&[seed, &counter.to_be_bytes()].concat()));
+    counter += 1;
+  }
+  mask.truncate(length);
+  mask
+}
+
+// EMSA-PSS encoding (RFC 8017, 9.1.1) with SHA-1 and MGF1-SHA1. em_bits is the size of the modulus minus one.
+// Returns None if em_bits is too small for the digest, the salt and the two fixed bytes
+pub fn emsa_pss_encode(m_hash: &Sha1Digest, salt: &[u8], em_bits: usize) -> Option<Vec<u8>> {
+  let em_len = em_bits.div_ceil(8);
+  let h_len = m_hash.len();
+  if em_len < h_len + salt.len() + 2 {
+    return None;
+  }
+  let h = Sha1::hash(&[[0u8; 8].as_slice(), m_hash, salt].concat());
+  let ps = vec![0u8; em_len - salt.len() - h_len - 2];
+  let db = [ps.as_slice(), &[0x01], salt].concat();
+  let db_mask = mgf1(&h, em_len - h_len - 1, &sha1_vec);
+  let mut masked_db: Vec<u8> = db.iter().zip(db_mask).map(|(a, b)| a ^ b).collect();
+  masked_db[0] &= 0xff >> (8 * em_len - em_bits);
+  Some([masked_db.as_slice(), &h, &[0xbc]].concat())
+}
+
+// EMSA-PSS verification (RFC 8017, 9.1.2): unmasks DB, recovers the salt and compares H with H'
+pub fn emsa_pss_verify(m_hash: &Sha1Digest, em: &[u8], em_bits: usize, salt_len: usize) -> bool {
+  let em_len = em_bits.div_ceil(8);
+  let h_len = m_hash.len();
+  if em.len() != em_len || em_len < h_len + salt_len + 2 || em[em_len - 1] != 0xbc {
+    return false;
+  }
+  let (masked_db, h) = (&em[..em_len - h_len - 1], &em[em_len - h_len - 1..em_len - 1]);
+  let unused_bits_mask = !(0xffu8 >> (8 * em_len - em_bits));
+  if masked_db[0] & unused_bits_mask != 0 {
+    return false;
+  }
+  let db_mask = mgf1(h, em_len - h_len - 1, &sha1_vec);
+  let mut db: Vec<u8> = masked_db.iter().zip(db_mask).map(|(a, b)| a ^ b).collect();
+  db[0] &= !unused_bits_mask;
+  let ps_len = em_len - h_len - salt_len - 2;
+  if db[..ps_len].iter().any(|&b| b != 0) || db[ps_len] != 0x01 {
+    return false;
+  }
+  let salt = &db[db.len() - salt_len..];
+  let h_prime = Sha1::hash(&[[0u8; 8].as_slice(), m_hash, salt].concat());
+  h == h_prime
+}
+
+fn sha1_vec(data: &[u8]) -> Vec<u8> {
+  Sha1::hash(&data).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::conversion::hex_string::HexString;
+
+  fn sha1(data: &[u8]) -> Vec<u8> {
+    sha1_vec(data)
+  }
+
+  #[test]
+  fn test_mgf1_sha1() {
+    assert_eq!(HexString::try_from(mgf1(b"foo", 3, &sha1)).unwrap(), HexString::try_from("1ac907").unwrap());
+    assert_eq!(HexString::try_from(mgf1(b"foo", 5, &sha1)).unwrap(), HexString::try_from("1ac9075cd4").unwrap());
+    assert_eq!(
+      HexString::try_from(mgf1(b"bar", 50, &sha1)).unwrap(),
+      HexString::try_from(
+        "bc0c655e016bc2931d85a2e675181adcef7f581f76df2739da74faac41627be2f7f415c89e983fd0ce80ced9878641cb4876"
+      )
+      .unwrap()
+    );
+    assert!(mgf1(b"foo", 0, &sha1).is_empty());
+  }
+
+  #[test]
+  fn test_oaep_pad_and_unpad() {
+    let k = 128;
+    for msg in [&b""[..], b"BOCA", &[0x01; 86]] {
+      let em = oaep_pad(msg, k, b"label").unwrap();
+      assert_eq!(em.len(), k);
+      assert_eq!(oaep_unpad(&em, k, b"label").unwrap(), msg);
+      assert_eq!(oaep_unpad(&em, k, b"other label"), Err(OAEPError::DecodingError));
+    }
+    // The seed is random, so the same message is encoded differently every time
+    assert_ne!(oaep_pad(b"BOCA", k, b"").unwrap(), oaep_pad(b"BOCA", k, b"").unwrap());
+    assert_eq!(oaep_pad(&[0; 87], k, b""), Err(OAEPError::MessageTooLong(87, 86)));
+    assert_eq!(oaep_pad(b"", 41, b""), Err(OAEPError::ModulusTooSmall(41)));
+    assert_eq!(oaep_pad(b"", 42, b"").unwrap().len(), 42);
+    let mut em = oaep_pad(b"BOCA", k, b"").unwrap();
+    em[0] = 0x01;
+    assert_eq!(oaep_unpad(&em, k, b""), Err(OAEPError::DecodingError));
+    assert_eq!(oaep_unpad(&em[1..], k, b""), Err(OAEPError::DecodingError));
+  }
+
+  #[test]
+  fn test_emsa_pkcs1_v15() {
+    let digest = Sha1::hash(b"hi mom");
+    let em = emsa_pkcs1_v15_encode(&digest, 128).unwrap();
+    assert_eq!(em.len(), 128);
+    assert!(emsa_pkcs1_v15_verify(&digest, &em));
+    assert!(!emsa_pkcs1_v15_verify(&Sha1::hash(b"hi dad"), &em));
+    // 7 bytes of padding are too few, both to encode and to verify
+    assert_eq!(emsa_pkcs1_v15_encode(&digest, 45), None);
+    let short_padding = [[0x00, 0x01].as_slice(), &[0xff; 7], &[0x00], &SHA1_DIGEST_INFO_PREFIX, &digest].concat();
+    assert!(!emsa_pkcs1_v15_verify(&digest, &short_padding));
+    assert!(emsa_pkcs1_v15_verify(&digest, &emsa_pkcs1_v15_encode(&digest, 46).unwrap()));
+    // Nothing may follow the digest
+    assert!(!emsa_pkcs1_v15_verify(&digest, &[em.as_slice(), &[0x00]].concat()));
+  }
+
+  #[test]
+  fn test_zero_pad_and_unpad() {
+    let zeros = [b"sixteen zeros...", &[0u8; 16][..]].concat();
+    for bytes in [&b""[..], b"BOCA", b"YELLOW SUBMARINE", b"ends in zeros\0\0", &zeros] {
+      let padded = zero_pad(bytes, 16);
+      assert!(padded.len().is_multiple_of(16) && padded.len() - bytes.len() < 16);
+      assert_eq!(zero_unpad(&padded, bytes.len()).unwrap(), bytes);
+    }
+    // Without the length the trailing zeros of the data can't be told apart from the padding
+    assert_eq!(zero_pad(b"A\0", 4), zero_pad(b"A", 4));
+    assert_eq!(zero_unpad(b"A\0\0\0", 1), Some(b"A".to_vec()));
+    assert_eq!(zero_unpad(b"AB\0\0", 1), None);
+    assert_eq!(zero_unpad(b"A\0\0\0", 5), None);
+  }
+}
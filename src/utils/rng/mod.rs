@@ -1,2 +1,3 @@
 pub mod mt19937;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod randomness_tests;
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use super::constants::*;
 
 pub struct MT19937TwisterRNG {
@@ -21,6 +23,22 @@ impl MT19937TwisterRNG {
     Self { states, index: 0 }
   }
 
+  pub fn state(&self) -> [u32; N] {
+    self.states
+  }
+
+  // Position in the state array of the next number to be tempered
+  pub fn index(&self) -> usize {
+    self.index
+  }
+
+  // Splices an arbitrary state (e.g. partially recovered from outputs) into the generator
+  pub fn set_state(&mut self, states: [u32; N], index: usize) {
+    assert!(index <= N);
+    self.states = states;
+    self.index = index;
+  }
+
   pub fn extract_number(&mut self) -> u32 {
     // Need to generate N new numbers
     if self.index == N {
@@ -31,6 +49,41 @@ impl MT19937TwisterRNG {
     Self::temper(y)
   }
 
+  // Uniform in [lo, hi). Outputs from the last incomplete copy of the range are rejected, so there is no modulo bias
+  pub fn gen_range(&mut self, lo: u32, hi: u32) -> u32 {
+    assert!(lo < hi, "The range [{lo}, {hi}) is empty");
+    let span = (hi - lo) as u64;
+    let limit = (1u64 << 32) - (1u64 << 32) % span;
+    loop {
+      let x = self.extract_number() as u64;
+      if x < limit {
+        return lo + (x % span) as u32;
+      }
+    }
+  }
+
+  pub fn gen_bytes(&mut self, n: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(n + 3);
+    while bytes.len() < n {
+      bytes.extend_from_slice(&self.extract_number().to_le_bytes());
+    }
+    bytes.truncate(n);
+    bytes
+  }
+
+  // PHP's mt_srand(seed). Unlike initialize, which outputs the initial words before twisting them,
+  // the state is twisted before the first output, as in the reference implementation
+  pub fn php_mt_srand(seed: u32) -> Self {
+    let mut rng = Self::initialize(seed);
+    rng.index = N;
+    rng
+  }
+
+  // PHP's mt_rand() without arguments, which drops the lowest bit of the output
+  pub fn php_mt_rand(&mut self) -> u32 {
+    self.extract_number() >> 1
+  }
+
   fn twist(&mut self) {
     for i in 0..N {
       let x = (self.states[i] & UMASK) | (self.states[(i + 1) % N] & LMASK);
@@ -53,6 +106,27 @@ impl MT19937TwisterRNG {
   }
 }
 
+// First output of the generator seeded with php_mt_srand(seed). The first twisted word only depends on the words 0, 1 and M of
+// the initial state, so the initialization stops there instead of computing all N words
+fn first_output(seed: u32) -> u32 {
+  let mut states = [0u32; M + 1];
+  states[0] = seed;
+  for i in 1..=M {
+    states[i] = (states[i - 1] ^ (states[i - 1] >> (W - 2)))
+      .wrapping_mul(F)
+      .wrapping_add(i as u32);
+  }
+  let x = (states[0] & UMASK) | (states[1] & LMASK);
+  let x_a = if x & 1 != 0 { (x >> 1) ^ A } else { x >> 1 };
+  MT19937TwisterRNG::temper(states[M] ^ x_a)
+}
+
+// Seeds in range whose first php_mt_rand() output is output. A 31-bit output over a range much smaller
+// than 2^31 seeds usually matches only the real seed
+pub fn seed_from_single_output_bruteforce(output: u32, range: RangeInclusive<u32>) -> Vec<u32> {
+  range.filter(|&seed| first_output(seed) >> 1 == output).collect()
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -67,4 +141,62 @@ mod tests {
     assert_eq!(rng.extract_number(), 594287098);
     assert_eq!(rng.extract_number(), 3930198914);
   }
+
+  #[test]
+  fn test_set_state_clones_generator() {
+    let mut rng = MT19937TwisterRNG::initialize(5489);
+    for _ in 0..1000 {
+      rng.extract_number();
+    }
+    let mut clone = MT19937TwisterRNG::initialize(0);
+    clone.set_state(rng.state(), rng.index());
+    for _ in 0..2 * N {
+      assert_eq!(rng.extract_number(), clone.extract_number());
+    }
+  }
+
+  #[test]
+  fn test_gen_range_bounds_and_uniformity() {
+    let mut rng = MT19937TwisterRNG::initialize(5489);
+    let (lo, hi) = (10u32, 16u32);
+    let draws = 60000;
+    let mut counts = [0usize; 6];
+    for _ in 0..draws {
+      let x = rng.gen_range(lo, hi);
+      assert!(lo <= x && x < hi);
+      counts[(x - lo) as usize] += 1;
+    }
+    // Each value is expected 10000 times
+    for count in counts {
+      assert!(9000 < count && count < 11000);
+    }
+    assert_eq!(rng.gen_range(7, 8), 7);
+    let x = rng.gen_range(0, u32::MAX);
+    assert!(x < u32::MAX);
+  }
+
+  #[test]
+  fn test_gen_bytes() {
+    let mut rng1 = MT19937TwisterRNG::initialize(1234);
+    let mut rng2 = MT19937TwisterRNG::initialize(1234);
+    let bytes = rng1.gen_bytes(10);
+    assert_eq!(bytes.len(), 10);
+    assert_eq!(bytes[..4], rng2.extract_number().to_le_bytes());
+    assert!(rng1.gen_bytes(0).is_empty());
+  }
+
+  #[test]
+  fn test_php_mt_rand_seed_recovery() {
+    // PHP >= 7.1: mt_srand(1); mt_rand() gives 895547922
+    assert_eq!(MT19937TwisterRNG::php_mt_srand(1).php_mt_rand(), 895547922);
+    for seed in [0, 1, 31337, 1 << 20] {
+      assert_eq!(first_output(seed), MT19937TwisterRNG::php_mt_srand(seed).extract_number());
+    }
+
+    let seed = 1_700_000_123;
+    let output = MT19937TwisterRNG::php_mt_srand(seed).php_mt_rand();
+    let range = seed - 50_000..=seed + 50_000;
+    assert_eq!(seed_from_single_output_bruteforce(output, range), vec![seed]);
+    assert!(seed_from_single_output_bruteforce(output, 0..=1000).is_empty());
+  }
 }
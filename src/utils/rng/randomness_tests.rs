@@ -0,0 +1,112 @@
+/*
+  Statistical tests for keystreams and RNG output, as in NIST SP 800-22. Each statistic is compared against the
+  critical value of its distribution at the given confidence, so passing means that the output is not
+  distinguishable from random by that test (a truly random sequence still fails 1 - confidence of the time).
+  Bits are read from the most significant one of each byte
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confidence {
+  P95,
+  P99,
+  P999,
+}
+
+impl Confidence {
+  // z such that P(|Z| > z) = 1 - confidence for a standard normal Z
+  fn two_sided_z(&self) -> f64 {
+    match self {
+      Self::P95 => 1.960,
+      Self::P99 => 2.576,
+      Self::P999 => 3.291,
+    }
+  }
+
+  // z such that P(Z > z) = 1 - confidence
+  fn one_sided_z(&self) -> f64 {
+    match self {
+      Self::P95 => 1.645,
+      Self::P99 => 2.326,
+      Self::P999 => 3.090,
+    }
+  }
+}
+
+fn bits(bytes: &[u8]) -> impl Iterator<Item = bool> + '_ {
+  bytes.iter().flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+}
+
+// The number of ones minus the number of zeros is close to N(0, n) for n random bits
+pub fn monobit_test(bytes: &[u8], confidence: Confidence) -> bool {
+  let n = (8 * bytes.len()) as f64;
+  let ones = bytes.iter().map(|byte| byte.count_ones() as f64).sum::<f64>();
+  n > 0.0 && (2.0 * ones - n).abs() / n.sqrt() < confidence.two_sided_z()
+}
+
+// The number of runs (maximal blocks of equal bits) is close to N(2n pi (1 - pi), 4n (pi (1 - pi))^2),
+// pi being the proportion of ones. It is only meaningful if the monobit proportion is reasonable
+pub fn runs_test(bytes: &[u8], confidence: Confidence) -> bool {
+  let n = (8 * bytes.len()) as f64;
+  if n == 0.0 {
+    return false;
+  }
+  let pi = bytes.iter().map(|byte| byte.count_ones() as f64).sum::<f64>() / n;
+  if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+    return false;
+  }
+  let changes = bits(bytes).zip(bits(bytes).skip(1)).filter(|(a, b)| a != b).count();
+  let runs = (changes + 1) as f64;
+  let variance_root = 2.0 * n.sqrt() * pi * (1.0 - pi);
+  (runs - 2.0 * n * pi * (1.0 - pi)).abs() / variance_root < confidence.two_sided_z()
+}
+
+// Chi-squared statistic of the byte frequencies against the uniform distribution, with 255 degrees of freedom.
+// The critical value comes from the Wilson-Hilferty approximation, k (1 - 2/9k + z sqrt(2/9k))^3
+pub fn chi_squared_byte_test(bytes: &[u8], confidence: Confidence) -> bool {
+  if bytes.is_empty() {
+    return false;
+  }
+  let mut counts = [0u64; 256];
+  for &byte in bytes {
+    counts[byte as usize] += 1;
+  }
+  let expected = bytes.len() as f64 / 256.0;
+  let chi_squared: f64 = counts.iter().map(|&count| (count as f64 - expected).powi(2) / expected).sum();
+  let k: f64 = 255.0;
+  let critical = k * (1.0 - 2.0 / (9.0 * k) + confidence.one_sided_z() * (2.0 / (9.0 * k)).sqrt()).powi(3);
+  chi_squared < critical
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::{
+    aes::{aes::AES, utils::AESMode},
+    rng::mt19937::MT19937TwisterRNG,
+  };
+
+  #[test]
+  fn test_keystreams_pass() {
+    let ctr_keystream = AES::encode(&vec![0; 100_000], b"YELLOW SUBMARINE", AESMode::CTR(0)).unwrap();
+    let mt_output = MT19937TwisterRNG::initialize(5489).gen_bytes(100_000);
+    for keystream in [ctr_keystream, mt_output] {
+      assert!(monobit_test(&keystream, Confidence::P99));
+      assert!(runs_test(&keystream, Confidence::P99));
+      assert!(chi_squared_byte_test(&keystream, Confidence::P99));
+    }
+  }
+
+  #[test]
+  fn test_patterns_fail() {
+    let constant = vec![0xff; 1000];
+    assert!(!monobit_test(&constant, Confidence::P95));
+    assert!(!runs_test(&constant, Confidence::P95));
+    assert!(!chi_squared_byte_test(&constant, Confidence::P95));
+
+    // Balanced but alternating bits have too many runs, and only one byte value
+    let alternating = vec![0x55; 1000];
+    assert!(monobit_test(&alternating, Confidence::P95));
+    assert!(!runs_test(&alternating, Confidence::P95));
+    assert!(!chi_squared_byte_test(&alternating, Confidence::P95));
+  }
+}
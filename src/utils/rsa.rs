@@ -1,12 +1,59 @@
 use num_bigint::BigUint;
-use num_traits::One;
+use num::Integer;
+use num_traits::{One, Zero};
+use rand::{thread_rng, Rng};
 
-use super::{algebra::{modulo::{inv_mod, mod_exp}, primes::generate_prime}, padding::{pkcs1_pad, pkcs1_unpad}};
+use super::{
+  algebra::{modulo::{crt, inv_mod, mod_exp}, primes::generate_prime},
+  conversion::conversion::left_pad_to,
+  mac::sha1::Sha1,
+  padding::{
+    emsa_pkcs1_v15_encode, emsa_pkcs1_v15_verify, emsa_pss_encode, emsa_pss_verify, oaep_pad, oaep_unpad, pkcs1_pad,
+    pkcs1_unpad, OAEPError, OAEP_HASH_LEN,
+  },
+};
 
 #[derive(Clone)]
 pub struct RSAKeys {
   pub sk: (BigUint, BigUint), // (d,n)
   pub pk: (BigUint, BigUint), // (e,n)
+  pub crt: Option<RSACrtParams>, // Only known when the keys were built from p and q
+}
+
+// dP = d mod (p-1), dQ = d mod (q-1) and qInv = q^-1 mod p, as in the private keys of RFC 8017
+#[derive(Clone)]
+pub struct RSACrtParams {
+  pub p: BigUint,
+  pub q: BigUint,
+  pub d_p: BigUint,
+  pub d_q: BigUint,
+  pub q_inv: BigUint,
+}
+
+impl RSACrtParams {
+  // None if p and q are not coprime
+  pub fn new(p: &BigUint, q: &BigUint, d: &BigUint) -> Option<Self> {
+    Some(Self {
+      d_p: d % (p - BigUint::one()),
+      d_q: d % (q - BigUint::one()),
+      q_inv: inv_mod(&(q % p), p)?,
+      p: p.clone(),
+      q: q.clone(),
+    })
+  }
+
+  // c^d (mod n) from two exponentiations with half-sized exponents and moduli
+  pub fn private(&self, c: &BigUint) -> BigUint {
+    let s_p = mod_exp(&(c % &self.p), &self.d_p, &self.p);
+    let s_q = mod_exp(&(c % &self.q), &self.d_q, &self.q);
+    RSA::crt_recombine(&s_p, &s_q, self)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RSAPadding {
+  PKCS1,
+  OAEP { label: Vec<u8> },
 }
 
 pub struct RSA {}
@@ -15,77 +62,339 @@ impl RSA {
   const E: u64 = 65537;
   const BITS: u64 = 1024;
   const ITERATIONS: u64 = 7;
-  pub fn generate_keys() -> RSAKeys {
-    loop {
-      let p: BigUint = generate_prime(Self::BITS, Self::ITERATIONS);
-      let q: BigUint = generate_prime(Self::BITS, Self::ITERATIONS);
-      let n = &p * &q;
-      let et = (&p - BigUint::one()) * (&q - BigUint::one());
-      let option_d = inv_mod(&BigUint::from(Self::E), &et);
-      match option_d {
-        Some(d) => {
-          return RSAKeys {
-            sk: (d, n.clone()),
-            pk: (BigUint::from(Self::E), n),
-          };
-        }
-        None => continue,
-      }
-    }
+  pub fn generate_default_keys() -> RSAKeys {
+    Self::generate_keys(&BigUint::from(Self::E), Self::BITS, Self::ITERATIONS)
   }
 
   pub fn encrypt_with_key<S: AsRef<[u8]>>(pk: &(BigUint, BigUint), plaintext: &S) -> Vec<u8> {
+    let n_size = pk.1.bits().div_ceil(8) as usize;
+    let padded_chunks = plaintext.as_ref().chunks(n_size - 3).map(|chunk| pkcs1_pad(chunk, n_size));
+    Self::encrypt_padded_chunks(pk, padded_chunks)
+  }
+
+  // The plaintext is split in the biggest chunks the padding allows, each one encrypted on its own.
+  // OAEP fails if the modulus has no room for a single byte of message
+  pub fn encrypt_with_padding<S: AsRef<[u8]>>(
+    pk: &(BigUint, BigUint),
+    plaintext: &S,
+    padding: &RSAPadding,
+  ) -> Result<Vec<u8>, OAEPError> {
+    let label = match padding {
+      RSAPadding::PKCS1 => return Ok(Self::encrypt_with_key(pk, plaintext)),
+      RSAPadding::OAEP { label } => label,
+    };
+    let n_size = pk.1.bits().div_ceil(8) as usize;
+    let chunk_size = n_size
+      .checked_sub(2 * OAEP_HASH_LEN + 2)
+      .filter(|&chunk_size| chunk_size > 0)
+      .ok_or(OAEPError::ModulusTooSmall(n_size))?;
+    let padded_chunks = plaintext
+      .as_ref()
+      .chunks(chunk_size)
+      .map(|chunk| oaep_pad(chunk, n_size, label))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(Self::encrypt_padded_chunks(pk, padded_chunks))
+  }
+
+  fn encrypt_padded_chunks(pk: &(BigUint, BigUint), padded_chunks: impl IntoIterator<Item = Vec<u8>>) -> Vec<u8> {
     let (e, n) = pk;
-    let n_size = ((n.bits() + 7) / 8) as usize;
+    let n_size = n.bits().div_ceil(8) as usize;
     let mut ciphertext = Vec::new();
-    for chunk in plaintext.as_ref().chunks(n_size - 3) {
-      let padded_chunk = if chunk.len() < n_size {
-        pkcs1_pad(chunk, n_size)
-      } else {
-        chunk.to_vec()
-      };
+    for padded_chunk in padded_chunks {
       let m = BigUint::from_bytes_be(&padded_chunk);
-      let ciphertext_chunk = mod_exp(&m, &e, &n).to_bytes_be();
-      let zeros = n_size - ciphertext_chunk.len();
-      let with_trailing_zeros: Vec<u8> = [vec![0x00; zeros], ciphertext_chunk].concat();
-      ciphertext.extend_from_slice(&with_trailing_zeros);
+      let ciphertext_chunk = mod_exp(&m, e, n).to_bytes_be();
+      ciphertext.extend_from_slice(&left_pad_to(ciphertext_chunk, n_size).unwrap());
     }
     ciphertext
   }
 
-  pub fn decrypt_with_key<S: AsRef<[u8]>>(sk: &(BigUint, BigUint), ciphertext: &S) -> Vec<u8> {
+  // PKCS#1 v1.5 unpadding never fails here, OAEP fails if any chunk does not decode
+  pub fn decrypt_with_padding<S: AsRef<[u8]>>(
+    sk: &(BigUint, BigUint),
+    ciphertext: &S,
+    padding: &RSAPadding,
+  ) -> Result<Vec<u8>, OAEPError> {
     let (d, n) = sk;
-    let n_size = ((n.bits() + 7) / 8) as usize;
+    let label = match padding {
+      RSAPadding::PKCS1 => return Ok(Self::decrypt_with_key(sk, ciphertext)),
+      RSAPadding::OAEP { label } => label,
+    };
+    let n_size = n.bits().div_ceil(8) as usize;
     let mut plaintext = Vec::new();
     for chunk in ciphertext.as_ref().chunks(n_size) {
-      let m = BigUint::from_bytes_be(chunk);
-      let plaintext_chunk = mod_exp(&m, &d, &n).to_bytes_be();
-      let unpadded_chunk = {
-        let zeros = n_size - plaintext_chunk.len();
-        let with_trailing_zeros = [vec![0x00; zeros], plaintext_chunk].concat();
-        pkcs1_unpad(&with_trailing_zeros)
-      };
+      let em = left_pad_to(mod_exp(&BigUint::from_bytes_be(chunk), d, n).to_bytes_be(), n_size)
+        .map_err(|_| OAEPError::DecodingError)?;
+      plaintext.extend(oaep_unpad(&em, n_size, label)?);
+    }
+    Ok(plaintext)
+  }
+
+  pub fn decrypt_with_key<S: AsRef<[u8]>>(sk: &(BigUint, BigUint), ciphertext: &S) -> Vec<u8> {
+    let (d, n) = sk;
+    Self::decrypt_chunks(n, ciphertext.as_ref(), |c| mod_exp(c, d, n))
+  }
+
+  // Same result as decrypt_with_key, using the CRT parameters when the keys have them
+  pub fn decrypt_with_key_crt<S: AsRef<[u8]>>(keys: &RSAKeys, ciphertext: &S) -> Vec<u8> {
+    match &keys.crt {
+      Some(crt) => Self::decrypt_chunks(&keys.sk.1, ciphertext.as_ref(), |c| crt.private(c)),
+      None => Self::decrypt_with_key(&keys.sk, ciphertext),
+    }
+  }
+
+  fn decrypt_chunks(n: &BigUint, ciphertext: &[u8], private: impl Fn(&BigUint) -> BigUint) -> Vec<u8> {
+    let n_size = n.bits().div_ceil(8) as usize;
+    let mut plaintext = Vec::new();
+    for chunk in ciphertext.chunks(n_size) {
+      let plaintext_chunk = private(&BigUint::from_bytes_be(chunk)).to_bytes_be();
+      let unpadded_chunk = pkcs1_unpad(&left_pad_to(plaintext_chunk, n_size).unwrap());
       plaintext.extend_from_slice(&unpadded_chunk);
     }
     plaintext
   }
 
-  // Pre: p, q are primes
+  // Textbook RSA, m^e (mod n), with no padding involved
+  pub fn raw_public(m: &BigUint, pk: &(BigUint, BigUint)) -> BigUint {
+    let (e, n) = pk;
+    mod_exp(m, e, n)
+  }
+
+  // Textbook RSA, c^d (mod n). Also the raw signing operation
+  pub fn raw_private(c: &BigUint, sk: &(BigUint, BigUint)) -> BigUint {
+    let (d, n) = sk;
+    mod_exp(c, d, n)
+  }
+
+  // c^d (mod n) computed with the CRT from the factors of n: s_p = c^(d mod p-1) (mod p), s_q = c^(d mod q-1) (mod q)
+  pub fn raw_private_crt(c: &BigUint, d: &BigUint, p: &BigUint, q: &BigUint) -> Option<BigUint> {
+    Some(RSACrtParams::new(p, q, d)?.private(c))
+  }
+
+  // Garner's formula, s = s_q + q * ((s_p - s_q) * q^-1 mod p)
+  fn crt_recombine(s_p: &BigUint, s_q: &BigUint, crt: &RSACrtParams) -> BigUint {
+    let (p, q) = (&crt.p, &crt.q);
+    let h = ((s_p + p - (s_q % p)) * &crt.q_inv) % p;
+    s_q + q * h
+  }
+
+  /*
+    Bellcore attack. If one half of a CRT signature is faulty, say s_q, the faulty signature s' is still correct
+    modulo p but not modulo q, so s - s' is a multiple of p and not of n: gcd(s - s', n) = p.
+    Both signatures must be of the same message, which is only needed to produce them. This is why CRT
+    implementations verify the signature (s^e = m mod n) before releasing it.
+    Returns None if the signatures are equal or the gcd is not a proper factor of n.
+  */
+  pub fn bellcore_fault_attack(
+    n: &BigUint,
+    faulty_signature: &BigUint,
+    correct_signature: &BigUint,
+    _message: &BigUint,
+  ) -> Option<BigUint> {
+    let difference = if faulty_signature > correct_signature {
+      faulty_signature - correct_signature
+    } else {
+      correct_signature - faulty_signature
+    };
+    let factor = difference.gcd(n);
+    if factor > BigUint::one() && &factor < n {
+      Some(factor)
+    } else {
+      None
+    }
+  }
+
+  // RSASSA-PKCS1-v1_5 with SHA-1. None if the modulus is too small for the DigestInfo and 8 bytes of padding
+  pub fn sign_pkcs1<S: AsRef<[u8]>>(message: &S, sk: &(BigUint, BigUint)) -> Option<Vec<u8>> {
+    let k = sk.1.bits().div_ceil(8) as usize;
+    let em = emsa_pkcs1_v15_encode(&Sha1::hash(message), k)?;
+    left_pad_to(Self::raw_private(&BigUint::from_bytes_be(&em), sk).to_bytes_be(), k).ok()
+  }
+
+  // The signature must be as long as the modulus and the whole block must be exactly the expected encoding
+  pub fn verify_pkcs1_signature<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+    pk: &(BigUint, BigUint),
+    message: &S,
+    signature: &T,
+  ) -> bool {
+    let n = &pk.1;
+    let k = n.bits().div_ceil(8) as usize;
+    let s = BigUint::from_bytes_be(signature.as_ref());
+    if signature.as_ref().len() != k || &s >= n {
+      return false;
+    }
+    match left_pad_to(Self::raw_public(&s, pk).to_bytes_be(), k) {
+      Ok(em) => emsa_pkcs1_v15_verify(&Sha1::hash(message), &em),
+      Err(_) => false,
+    }
+  }
+
+  // RSASSA-PSS with SHA-1 and a random salt of salt_len bytes. None if the modulus is too small for the salt
+  pub fn sign_pss<S: AsRef<[u8]>>(message: &S, sk: &(BigUint, BigUint), salt_len: usize) -> Option<Vec<u8>> {
+    let n = &sk.1;
+    let em_bits = (n.bits() - 1) as usize;
+    let mut salt = vec![0u8; salt_len];
+    thread_rng().fill(salt.as_mut_slice());
+    let em = emsa_pss_encode(&Sha1::hash(message), &salt, em_bits)?;
+    let s = Self::raw_private(&BigUint::from_bytes_be(&em), sk);
+    left_pad_to(s.to_bytes_be(), n.bits().div_ceil(8) as usize).ok()
+  }
+
+  pub fn verify_pss<S: AsRef<[u8]>, T: AsRef<[u8]>>(
+    message: &S,
+    signature: &T,
+    pk: &(BigUint, BigUint),
+    salt_len: usize,
+  ) -> bool {
+    let n = &pk.1;
+    if signature.as_ref().len() != n.bits().div_ceil(8) as usize {
+      return false;
+    }
+    let s = BigUint::from_bytes_be(signature.as_ref());
+    if &s >= n {
+      return false;
+    }
+    let em_bits = (n.bits() - 1) as usize;
+    let m = Self::raw_public(&s, pk);
+    match left_pad_to(m.to_bytes_be(), em_bits.div_ceil(8)) {
+      Ok(em) => emsa_pss_verify(&Sha1::hash(message), &em, em_bits, salt_len),
+      Err(_) => false,
+    }
+  }
+
+  // E(m1) * E(m2) = m1^e * m2^e = (m1 * m2)^e = E(m1 * m2) (mod n). Works chunk by chunk
+  // Pre: c1 and c2 have the same amount of chunks of size |n|
+  pub fn homomorphic_mul<S: AsRef<[u8]>, T: AsRef<[u8]>>(c1: &S, c2: &T, n: &BigUint) -> Vec<u8> {
+    let n_size = n.bits().div_ceil(8) as usize;
+    let mut result = Vec::new();
+    for (chunk1, chunk2) in c1.as_ref().chunks(n_size).zip(c2.as_ref().chunks(n_size)) {
+      let product = (BigUint::from_bytes_be(chunk1) * BigUint::from_bytes_be(chunk2)) % n;
+      result.extend_from_slice(&left_pad_to(product.to_bytes_be(), n_size).unwrap());
+    }
+    result
+  }
+
+  /*
+    Hastad's broadcast attack. The same m is encrypted (without padding) under e keys with exponent e:
+      c_i = m^e (mod n_i)
+    By CRT we obtain r = m^e (mod n_1 * ... * n_e). Since m < n_i for every i, m^e < n_1 * ... * n_e, so r = m^e
+    over the integers and m is its e-th root.
+
+    Padded extension: if m_i = a_i * m + b_i with known (a_i, b_i), the c_i are roots of the polynomials
+    g_i(x) = (a_i * x + b_i)^e - c_i (mod n_i). Combining them by CRT gives one monic polynomial of degree e
+    modulo N = n_1 * ... * n_e with the small root m, which can be found with Coppersmith's method (lattice
+    reduction). That is not implemented here: this function only handles the unpadded case.
+
+    Returns None if there are not enough ciphertexts, the moduli are not coprime or the result is not an e-th power.
+  */
+  pub fn hastad_broadcast(ciphertexts: &[BigUint], moduli: &[BigUint], e: u32) -> Option<BigUint> {
+    if e == 0 || ciphertexts.len() != moduli.len() || ciphertexts.len() < e as usize {
+      return None;
+    }
+    let r = crt(ciphertexts, moduli)?;
+    let m = r.nth_root(e);
+    if m.pow(e) == r {
+      Some(m)
+    } else {
+      None
+    }
+  }
+
+  // Textbook RSA with a small e and a short message: if m^e < n there is no modular reduction, so c = m^e over the
+  // integers and m is its e-th root. None if c is not a residue modulo n or not a perfect e-th power
+  pub fn rsa_recover_small_e3(ciphertext: &BigUint, e: u32, n: &BigUint) -> Option<Vec<u8>> {
+    if e == 0 || ciphertext >= n {
+      return None;
+    }
+    let m = ciphertext.nth_root(e);
+    if &m.pow(e) == ciphertext {
+      Some(m.to_bytes_be())
+    } else {
+      None
+    }
+  }
+
+  /*
+    Partial key exposure: the attacker knows p_high, the bits of p above the lowest unknown_bits ones.
+    Then p = p_high * 2^unknown_bits + x with 0 <= x < 2^unknown_bits, and the x dividing n is searched by brute force.
+    Coppersmith's method recovers p from half of its bits, this is the teaching-sized version.
+    Returns (p, q), or None if no candidate divides n or unknown_bits is too big to brute force.
+  */
+  pub fn recover_factors_from_partial_p(
+    n: &BigUint,
+    p_high: &BigUint,
+    unknown_bits: u32,
+  ) -> Option<(BigUint, BigUint)> {
+    const MAX_UNKNOWN_BITS: u32 = 32;
+    if unknown_bits > MAX_UNKNOWN_BITS {
+      return None;
+    }
+    let base = p_high << unknown_bits;
+    for x in 0..(1u64 << unknown_bits) {
+      let p = &base + x;
+      if p > BigUint::one() && &p < n && (n % &p).is_zero() {
+        let q = n / &p;
+        return Some((p, q));
+      }
+    }
+    None
+  }
+
+  // Same recovery for p and q of the same size, as in generated keys: p has half the bits of n, so the number of
+  // unknown bits follows from the known ones. The factors are only returned if e is a valid exponent for them
+  pub fn recover_private_from_partial(
+    n: &BigUint,
+    e: &BigUint,
+    known_high_bits_of_p: &BigUint,
+  ) -> Option<(BigUint, BigUint)> {
+    let unknown_bits = n.bits().div_ceil(2).checked_sub(known_high_bits_of_p.bits())?;
+    let (p, q) = Self::recover_factors_from_partial_p(n, known_high_bits_of_p, u32::try_from(unknown_bits).ok()?)?;
+    Self::keys_from_factors(&p, &q, e)?;
+    Some((p, q))
+  }
+
+  // The keys with public exponent e for the primes p and q. None if e is not invertible modulo (p - 1)(q - 1)
+  pub fn keys_from_factors(p: &BigUint, q: &BigUint, e: &BigUint) -> Option<RSAKeys> {
+    let n = p * q;
+    let et = (p - BigUint::one()) * (q - BigUint::one());
+    let d = inv_mod(e, &et)?;
+    Some(RSAKeys {
+      crt: RSACrtParams::new(p, q, &d),
+      sk: (d, n.clone()),
+      pk: (e.clone(), n),
+    })
+  }
+
   pub fn generate_keys_with_given_size(bits: u64) -> RSAKeys {
+    Self::generate_keys(&BigUint::from(Self::E), bits, Self::ITERATIONS)
+  }
+
+  pub fn generate_keys_with_given_params(e: &BigUint, bits: u64) -> RSAKeys {
+    Self::generate_keys(e, bits, Self::ITERATIONS)
+  }
+
+  // prime_bits is the size of each prime, tested with mr_iterations rounds of Miller-Rabin.
+  // Primes with p = 1 (mod e) are discarded right away, since e would divide p - 1. For a prime e that is enough
+  // for gcd(e, (p-1)(q-1)) = 1, otherwise the primes are regenerated until e is invertible
+  pub fn generate_keys(e: &BigUint, prime_bits: u64, mr_iterations: u64) -> RSAKeys {
+    let generate_suitable_prime = || loop {
+      let p = generate_prime(prime_bits, mr_iterations);
+      if !(&p % e).is_one() {
+        return p;
+      }
+    };
     loop {
-      let p: BigUint = generate_prime(bits, Self::ITERATIONS);
-      let q: BigUint = generate_prime(bits, Self::ITERATIONS);
+      let (p, q) = (generate_suitable_prime(), generate_suitable_prime());
+      if p == q {
+        continue;
+      }
       let n = &p * &q;
       let et = (&p - BigUint::one()) * (&q - BigUint::one());
-      let option_d = inv_mod(&BigUint::from(Self::E), &et);
-      match option_d {
-        Some(d) => {
-          return RSAKeys {
-            sk: (d, n.clone()),
-            pk: (BigUint::from(Self::E), n),
-          };
-        }
-        None => continue,
+      if let Some(d) = inv_mod(e, &et) {
+        return RSAKeys {
+          crt: RSACrtParams::new(&p, &q, &d),
+          sk: (d, n.clone()),
+          pk: (e.clone(), n),
+        };
       }
     }
   }
@@ -94,12 +403,99 @@ impl RSA {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::utils::{algebra::bigint_utils::cbrt, padding::SHA1_DIGEST_INFO_PREFIX};
   use num_bigint::RandBigInt;
-  use rand::thread_rng;
+
+  #[test]
+  fn test_raw_public_and_private_are_inverse() {
+    let rsa_keys = RSA::generate_keys_with_given_size(512);
+    let n = &rsa_keys.pk.1;
+    for m in [BigUint::zero(), BigUint::one(), BigUint::from(42u32), n - BigUint::one(), thread_rng().gen_biguint_below(n)] {
+      let c = RSA::raw_public(&m, &rsa_keys.pk);
+      assert_eq!(RSA::raw_private(&c, &rsa_keys.sk), m);
+      // Signing and verifying are the same operations in the opposite order
+      assert_eq!(RSA::raw_public(&RSA::raw_private(&m, &rsa_keys.sk), &rsa_keys.pk), m);
+    }
+  }
+
+  #[test]
+  fn test_rsa_bellcore_fault_attack() {
+    let e = BigUint::from(65537u32);
+    let (p, q, d) = loop {
+      let (p, q) = (generate_prime(256, 10), generate_prime(256, 10));
+      if p == q {
+        continue;
+      }
+      if let Some(d) = inv_mod(&e, &((&p - BigUint::one()) * (&q - BigUint::one()))) {
+        break (p, q, d);
+      }
+    };
+    let n = &p * &q;
+    let m = BigUint::from_bytes_be(b"BOCA YO TE AMO");
+    let correct = RSA::raw_private_crt(&m, &d, &p, &q).unwrap();
+    assert_eq!(correct, RSA::raw_private(&m, &(d.clone(), n.clone())));
+
+    // A fault flips one bit while computing the half modulo q
+    let s_p = mod_exp(&m, &(&d % (&p - BigUint::one())), &p);
+    let s_q = mod_exp(&m, &(&d % (&q - BigUint::one())), &q) ^ BigUint::one();
+    let faulty = RSA::crt_recombine(&s_p, &s_q, &RSACrtParams::new(&p, &q, &d).unwrap());
+    assert_ne!(RSA::raw_public(&faulty, &(e, n.clone())), m);
+
+    assert_eq!(RSA::bellcore_fault_attack(&n, &faulty, &correct, &m), Some(p));
+    assert_eq!(RSA::bellcore_fault_attack(&n, &correct, &correct, &m), None);
+  }
+
+  #[test]
+  fn test_pkcs1_signature_rejects_forgery() {
+    let rsa_keys = RSA::generate_keys(&BigUint::from(3u32), 512, 7);
+    let message = b"hi mom";
+    let signature = RSA::sign_pkcs1(message, &rsa_keys.sk).unwrap();
+    assert!(RSA::verify_pkcs1_signature(&rsa_keys.pk, message, &signature));
+    assert!(!RSA::verify_pkcs1_signature(&rsa_keys.pk, b"hi dad", &signature));
+    assert!(!RSA::verify_pkcs1_signature(&rsa_keys.pk, message, &signature[1..].to_vec()));
+
+    // Bleichenbacher's e = 3 forgery: a short padding and the digest, followed by garbage that makes it a cube
+    let k = rsa_keys.pk.1.bits().div_ceil(8) as usize;
+    let short_padding = [0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0x00];
+    let prefix = [short_padding.as_slice(), &SHA1_DIGEST_INFO_PREFIX, &Sha1::hash(message)].concat();
+    let block = [prefix.as_slice(), &vec![0x00; k - prefix.len()]].concat();
+    let root = cbrt(&BigUint::from_bytes_be(&block)) + BigUint::one();
+    let forged = left_pad_to(root.to_bytes_be(), k).unwrap();
+    let em = left_pad_to(RSA::raw_public(&root, &rsa_keys.pk).to_bytes_be(), k).unwrap();
+    assert_eq!(em[..prefix.len()], prefix);
+    assert!(!RSA::verify_pkcs1_signature(&rsa_keys.pk, message, &forged));
+  }
+
+  #[test]
+  fn test_pss_sign_and_verify() {
+    let rsa_keys = RSA::generate_keys_with_given_size(512);
+    let message = b"BOCA YO TE AMO";
+    for salt_len in [0, 20] {
+      let signature = RSA::sign_pss(message, &rsa_keys.sk, salt_len).unwrap();
+      assert_eq!(signature.len(), rsa_keys.pk.1.bits().div_ceil(8) as usize);
+      assert!(RSA::verify_pss(message, &signature, &rsa_keys.pk, salt_len));
+      assert!(!RSA::verify_pss(b"BOCA YO TE AMO!", &signature, &rsa_keys.pk, salt_len));
+    }
+    // The modulus has 1024 bits, there is no room for a salt of 110 bytes
+    assert!(RSA::sign_pss(message, &rsa_keys.sk, 110).is_none());
+  }
+
+  #[test]
+  fn test_pss_modified_signature_fails() {
+    let rsa_keys = RSA::generate_keys_with_given_size(512);
+    let message = b"BOCA YO TE AMO";
+    let signature = RSA::sign_pss(message, &rsa_keys.sk, 20).unwrap();
+    for i in [0, signature.len() / 2, signature.len() - 1] {
+      let mut modified = signature.clone();
+      modified[i] ^= 0x01;
+      assert!(!RSA::verify_pss(message, &modified, &rsa_keys.pk, 20));
+    }
+    assert!(!RSA::verify_pss(message, &signature[1..].to_vec(), &rsa_keys.pk, 20));
+  }
 
   #[test]
   fn test_rsa_small_numbers() {
-    let rsa_keys = RSA::generate_keys();
+    let rsa_keys = RSA::generate_default_keys();
     let plaintext = 42u8.to_be_bytes().to_vec();
     let ciphertext = RSA::encrypt_with_key(&rsa_keys.pk, &plaintext);
     assert_eq!(plaintext, RSA::decrypt_with_key(&rsa_keys.sk, &ciphertext));
@@ -107,7 +503,7 @@ mod tests {
 
   #[test]
   fn test_rsa_big_numbers() {
-    let rsa_keys = RSA::generate_keys();
+    let rsa_keys = RSA::generate_default_keys();
     let mut rng = thread_rng();
     // Remember it should be sized <= 2048 bits, if not, separate in chunks
     let plaintext = rng.gen_biguint(1024).to_bytes_be();
@@ -117,9 +513,150 @@ mod tests {
 
   #[test]
   fn test_rsa_text() {
-    let rsa_keys: RSAKeys = RSA::generate_keys();
+    let rsa_keys: RSAKeys = RSA::generate_default_keys();
     let plaintext = b"SOY BOSTERO DE LA CUNA A LA TUMBA Y NUNCA DESCENDERE".to_vec();
     let ciphertext = RSA::encrypt_with_key(&rsa_keys.pk, &plaintext);
     assert_eq!(plaintext, RSA::decrypt_with_key(&rsa_keys.sk, &ciphertext));
   }
+
+  #[test]
+  fn test_rsa_homomorphic_mul() {
+    let rsa_keys = RSA::generate_keys_with_given_size(128);
+    let ((e, n), (d, _)) = (&rsa_keys.pk, &rsa_keys.sk);
+    let (m1, m2) = (BigUint::from(123456789u32), BigUint::from(987654321u32));
+    let (c1, c2) = (mod_exp(&m1, e, n).to_bytes_be(), mod_exp(&m2, e, n).to_bytes_be());
+    let n_size = n.bits().div_ceil(8) as usize;
+    let (c1, c2) = (left_pad_to(c1, n_size).unwrap(), left_pad_to(c2, n_size).unwrap());
+    let product = RSA::homomorphic_mul(&c1, &c2, n);
+    assert_eq!(product.len(), n_size);
+    let decrypted = mod_exp(&BigUint::from_bytes_be(&product), d, n);
+    assert_eq!(decrypted, (m1 * m2) % n);
+  }
+
+  #[test]
+  fn test_rsa_oaep() {
+    // 1024-bit modulus
+    let rsa_keys = RSA::generate_keys_with_given_size(512);
+    let oaep = RSAPadding::OAEP { label: b"BOCA".to_vec() };
+    for plaintext in [b"SOY BOSTERO".to_vec(), b"SOY BOSTERO DE LA CUNA A LA TUMBA Y NUNCA DESCENDERE".repeat(4)] {
+      let ciphertext = RSA::encrypt_with_padding(&rsa_keys.pk, &plaintext, &oaep).unwrap();
+      assert_eq!(RSA::decrypt_with_padding(&rsa_keys.sk, &ciphertext, &oaep), Ok(plaintext.clone()));
+      let other_label = RSAPadding::OAEP { label: vec![] };
+      assert_eq!(RSA::decrypt_with_padding(&rsa_keys.sk, &ciphertext, &other_label), Err(OAEPError::DecodingError));
+      // A PKCS#1 v1.5 ciphertext is not valid OAEP
+      let pkcs1_ciphertext = RSA::encrypt_with_padding(&rsa_keys.pk, &plaintext, &RSAPadding::PKCS1).unwrap();
+      assert_eq!(RSA::decrypt_with_padding(&rsa_keys.sk, &pkcs1_ciphertext, &RSAPadding::PKCS1), Ok(plaintext));
+      assert_eq!(RSA::decrypt_with_padding(&rsa_keys.sk, &pkcs1_ciphertext, &oaep), Err(OAEPError::DecodingError));
+    }
+    // A 256-bit modulus has no room for OAEP
+    let small_keys = RSA::generate_keys_with_given_size(128);
+    assert_eq!(RSA::encrypt_with_padding(&small_keys.pk, b"BOCA", &oaep), Err(OAEPError::ModulusTooSmall(32)));
+  }
+
+  #[test]
+  fn test_rsa_decrypt_with_crt() {
+    let rsa_keys = RSA::generate_default_keys();
+    let crt = rsa_keys.crt.as_ref().unwrap();
+    assert_eq!(&crt.p * &crt.q, rsa_keys.pk.1);
+    let plaintext = b"SOY BOSTERO DE LA CUNA A LA TUMBA Y NUNCA DESCENDERE".repeat(5);
+    let ciphertext = RSA::encrypt_with_key(&rsa_keys.pk, &plaintext);
+    assert_eq!(RSA::decrypt_with_key_crt(&rsa_keys, &ciphertext), plaintext);
+    assert_eq!(RSA::decrypt_with_key_crt(&rsa_keys, &ciphertext), RSA::decrypt_with_key(&rsa_keys.sk, &ciphertext));
+
+    // Keys without the factors fall back to the single exponentiation
+    let without_crt = RSAKeys { crt: None, ..rsa_keys };
+    assert_eq!(RSA::decrypt_with_key_crt(&without_crt, &ciphertext), plaintext);
+  }
+
+  #[test]
+  fn test_rsa_generate_keys_with_given_params() {
+    let plaintext = b"BOCA".to_vec();
+    for e in [3u32, 65537u32] {
+      let rsa_keys = RSA::generate_keys_with_given_params(&BigUint::from(e), 64);
+      assert_eq!(rsa_keys.pk.0, BigUint::from(e));
+      let ciphertext = RSA::encrypt_with_key(&rsa_keys.pk, &plaintext);
+      assert_eq!(plaintext, RSA::decrypt_with_key(&rsa_keys.sk, &ciphertext));
+    }
+  }
+
+  #[test]
+  fn test_rsa_generate_keys() {
+    let e = BigUint::from(65537u32);
+    let rsa_keys = RSA::generate_keys(&e, 256, 10);
+    let crt = rsa_keys.crt.as_ref().unwrap();
+    let phi = (&crt.p - BigUint::one()) * (&crt.q - BigUint::one());
+    assert!(e.gcd(&phi).is_one());
+    assert_eq!((&rsa_keys.sk.0 * &e) % &phi, BigUint::one());
+    let plaintext = b"SOY BOSTERO DE LA CUNA A LA TUMBA".to_vec();
+    let ciphertext = RSA::encrypt_with_key(&rsa_keys.pk, &plaintext);
+    assert_eq!(RSA::decrypt_with_key(&rsa_keys.sk, &ciphertext), plaintext);
+  }
+
+  #[test]
+  fn test_rsa_hastad_broadcast() {
+    let e = BigUint::from(3u8);
+    let m = BigUint::from_bytes_be(b"BOCA JUNIORS");
+    let keys: Vec<RSAKeys> = (0..3).map(|_| RSA::generate_keys_with_given_params(&e, 64)).collect();
+    let moduli: Vec<BigUint> = keys.iter().map(|k| k.pk.1.clone()).collect();
+    let ciphertexts: Vec<BigUint> = moduli.iter().map(|n| mod_exp(&m, &e, n)).collect();
+    assert_eq!(RSA::hastad_broadcast(&ciphertexts, &moduli, 3), Some(m));
+    assert_eq!(RSA::hastad_broadcast(&ciphertexts[..2], &moduli[..2], 3), None);
+  }
+
+  #[test]
+  fn test_rsa_broadcast_with_crt() {
+    let e = BigUint::from(3u8);
+    let m = BigUint::from_bytes_be(b"AGUANTE BOQUITA");
+    let moduli: Vec<BigUint> = (0..3).map(|_| RSA::generate_keys_with_given_params(&e, 128).pk.1).collect();
+    let ciphertexts: Vec<BigUint> = moduli.iter().map(|n| mod_exp(&m, &e, n)).collect();
+    let cube = crt(&ciphertexts, &moduli).unwrap();
+    assert!(cube < moduli.iter().product());
+    assert_eq!(cbrt(&cube), m);
+  }
+
+  #[test]
+  fn test_rsa_recover_small_e3() {
+    let e = BigUint::from(3u8);
+    let keys = RSA::generate_keys_with_given_params(&e, 512);
+    let n = &keys.pk.1;
+    let plaintext = b"BOCA JUNIORS".to_vec();
+    let c = RSA::raw_public(&BigUint::from_bytes_be(&plaintext), &keys.pk);
+    assert_eq!(RSA::rsa_recover_small_e3(&c, 3, n), Some(plaintext));
+    assert_eq!(RSA::rsa_recover_small_e3(&(c + BigUint::one()), 3, n), None);
+  }
+
+  #[test]
+  fn test_rsa_recover_from_partial_p() {
+    let e = BigUint::from(65537u32);
+    // Two primes of exactly 32 bits
+    let generate_32_bit_prime = || loop {
+      let p = generate_prime(32, 10);
+      if p.bits() == 32 {
+        return p;
+      }
+    };
+    let (p, q) = loop {
+      let (p, q) = (generate_32_bit_prime(), generate_32_bit_prime());
+      if p != q && inv_mod(&e, &((&p - BigUint::one()) * (&q - BigUint::one()))).is_some() {
+        break (p, q);
+      }
+    };
+    let n = &p * &q;
+    let p_high = &p >> 16u32;
+
+    // q could share the high bits of p, in which case both factorizations are found
+    let (p_found, q_found) = RSA::recover_factors_from_partial_p(&n, &p_high, 16).unwrap();
+    assert!((p_found == p && q_found == q) || (p_found == q && q_found == p));
+    assert_eq!(RSA::recover_private_from_partial(&n, &e, &p_high), Some((p_found.clone(), q_found.clone())));
+
+    let keys = RSA::keys_from_factors(&p_found, &q_found, &e).unwrap();
+    let plaintext = b"BOCA".to_vec();
+    let ciphertext = RSA::encrypt_with_key(&keys.pk, &plaintext);
+    assert_eq!(RSA::decrypt_with_key(&keys.sk, &ciphertext), plaintext);
+
+    assert_eq!(RSA::recover_factors_from_partial_p(&n, &p_high, 40), None);
+    // e = 2 is never invertible modulo (p - 1)(q - 1)
+    assert_eq!(RSA::recover_private_from_partial(&n, &BigUint::from(2u32), &p_high), None);
+  }
+
 }
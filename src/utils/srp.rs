@@ -2,7 +2,7 @@ use num_bigint::{BigUint, RandBigInt};
 use num_traits::{One, Zero};
 use rand::thread_rng;
 use sha2::{Digest, Sha256};
-use crate::utils::mac::{hmac::Sha1HMac, sha1::Sha1Digest};
+use crate::utils::mac::hmac::{Sha1HMac, Sha256HMac};
 
 use super::algebra::{bigint_utils::concat_biguints, modulo::mod_exp, primes::get_nist_prime};
 
@@ -106,6 +106,7 @@ pub struct SrpSimulator {
   pub k: BigUint,
   _email: String,
   password: String,
+  use_sha256_hmac: bool,
 }
 
 impl SrpSimulator {
@@ -119,6 +120,23 @@ impl SrpSimulator {
       k,
       _email: email.clone(),
       password: password.clone(),
+      use_sha256_hmac: false,
+    }
+  }
+
+  // Authenticates with HMAC-SHA256 as the protocol says, instead of the default Sha1HMac
+  pub fn with_sha256_hmac(mut self) -> Self {
+    self.use_sha256_hmac = true;
+    self
+  }
+
+  // C sends the digest HMAC(client_key, salt) to S, which validates it with HMAC(server_key, salt)
+  fn exchange_hmac(&self, client_key: &[u8], server_key: &[u8]) -> bool {
+    let salt = self.server.salt.to_bytes_be();
+    if self.use_sha256_hmac {
+      Sha256HMac::new(&server_key).verify(&salt, Sha256HMac::new(&client_key).authenticate(&salt))
+    } else {
+      Sha1HMac::new(&server_key).verify(&salt, Sha1HMac::new(&client_key).authenticate(&salt))
     }
   }
 
@@ -141,13 +159,8 @@ impl SrpSimulator {
     );
     assert_eq!(server_key, client_key);
 
-    // C sends the digest HMAC(K, salt) to S. I use Sha1HMac because I implemented it, instead of HMAC-SHA256
-    let hmac = Sha1HMac::new(&client_key);
-    let client_digest: Sha1Digest = hmac.authenticate(&self.server.salt.to_bytes_be());
-
-    // S validates HMAC(K, salt)
-    let hmac = Sha1HMac::new(&server_key);
-    hmac.verify(&self.server.salt.to_bytes_be(), client_digest)
+    // C sends the digest HMAC(K, salt) to S, and S validates it
+    self.exchange_hmac(&client_key, &server_key)
   }
 
   pub fn bypass_with_zero_pk(&self) -> bool {
@@ -164,10 +177,7 @@ impl SrpSimulator {
     };
     assert_eq!(key, attacker_key);
 
-    let attacker_hmac = Sha1HMac::new(&attacker_key);
-    let attacker_digest: Sha1Digest = attacker_hmac.authenticate(&self.server.salt.to_bytes_be());
-    let hmac = Sha1HMac::new(&key);
-    hmac.verify(&self.server.salt.to_bytes_be(), attacker_digest)
+    self.exchange_hmac(&attacker_key, &key)
   }
 }
 
@@ -181,6 +191,8 @@ mod tests {
     let password = String::from("abcdefghijklm");
     let srp = SrpSimulator::for_email_password(&email, &password);
     assert!(srp.validate());
+    let srp = SrpSimulator::for_email_password(&email, &password).with_sha256_hmac();
+    assert!(srp.validate());
   }
 
   #[test]
@@ -189,5 +201,7 @@ mod tests {
     let password = String::from("abcdefghijklm");
     let srp = SrpSimulator::for_email_password(&email, &password);
     assert!(srp.bypass_with_zero_pk());
+    let srp = SrpSimulator::for_email_password(&email, &password).with_sha256_hmac();
+    assert!(srp.bypass_with_zero_pk());
   }
 }
@@ -0,0 +1,164 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::thread_rng;
+use sha2::{Digest, Sha256};
+use crate::utils::mac::{hmac::Sha1HMac, sha1::Sha1Digest};
+
+use super::{
+  algebra::{modulo::mod_exp, primes::get_nist_prime},
+  srp::salt_then_hash_biguint,
+};
+
+// ODA == Offline-Dictionary-Attack. Simplified SRP: B = g**b % N and u is a random 128 bit number
+struct ServerAbstractionODA {
+  salt: BigUint,
+  v: BigUint,
+  u: BigUint,
+  sk: BigUint,
+  pub pk: BigUint,
+}
+
+impl ServerAbstractionODA {
+  fn define_server(password: &String, n: &BigUint, g: &BigUint) -> Self {
+    let salt = thread_rng().gen_biguint_below(&(BigUint::one() << 256));
+    let x = salt_then_hash_biguint(&salt, password);
+    let v = mod_exp(g, &x, n);
+    let sk = thread_rng().gen_biguint_below(n);
+    let pk = mod_exp(g, &sk, n);
+    let u = thread_rng().gen_biguint_below(&(BigUint::one() << 128));
+    Self { salt, v, u, sk, pk }
+  }
+
+  fn compute_key(&self, n: &BigUint, client_pk: &BigUint, u: &BigUint) -> Vec<u8> {
+    let w = (client_pk * mod_exp(&self.v, u, n)) % n;
+    let s = mod_exp(&w, &self.sk, n);
+    let mut hasher = Sha256::new();
+    hasher.update(s.to_bytes_be());
+    hasher.finalize().to_vec()
+  }
+}
+
+struct ClientAbstractionODA {
+  sk: BigUint,
+  pub pk: BigUint,
+}
+
+impl ClientAbstractionODA {
+  fn define_client(n: &BigUint, g: &BigUint) -> Self {
+    let sk = thread_rng().gen_biguint_below(n);
+    let pk = mod_exp(g, &sk, n);
+    Self { sk, pk }
+  }
+
+  fn compute_key(&self, password: &String, n: &BigUint, server_pk: &BigUint, salt: &BigUint, u: &BigUint) -> Vec<u8> {
+    let x = salt_then_hash_biguint(salt, password);
+    // Generate S = B ** (a + u*x) % n
+    let s: BigUint = mod_exp(server_pk, &(&self.sk + u * x), n);
+    let mut hasher = Sha256::new();
+    hasher.update(s.to_bytes_be());
+    hasher.finalize().to_vec()
+  }
+}
+
+pub struct SrpSimulatorODA {
+  server: ServerAbstractionODA,
+  client: ClientAbstractionODA,
+  pub n: BigUint,
+  pub g: BigUint,
+  pub k: BigUint,
+  _email: String,
+  password: String,
+  // HMAC(K, 0) sent by C when M impersonates S with salt = 0, u = 1
+  client_digest: Sha1Digest,
+}
+
+impl SrpSimulatorODA {
+  pub fn for_email_password(email: &str, password: &String) -> Self {
+    let (n, g, k) = (get_nist_prime(), BigUint::from(2u32), BigUint::from(3u32));
+    let server = ServerAbstractionODA::define_server(password, &n, &g);
+    let client = ClientAbstractionODA::define_client(&n, &g);
+
+    // M does NOT know the password. He sends arbitrary (salt, s_pk, u) to C and captures its digest.
+    // M chooses salt = 0, u = 1 to simplify the computations
+    let (salt, u) = (BigUint::zero(), BigUint::one());
+    let client_key = client.compute_key(password, &n, &server.pk, &salt, &u);
+    let client_digest = Sha1HMac::new(&client_key).authenticate(&salt.to_bytes_be());
+
+    Self {
+      server,
+      client,
+      n,
+      g,
+      k,
+      _email: email.to_string(),
+      password: password.clone(),
+      client_digest,
+    }
+  }
+
+  pub fn validate(&self) -> bool {
+    // C sends (email, c_pk) to S. S sends (salt, s_pk, u) to C
+    // Both generate the key K
+    let server_key = self.server.compute_key(&self.n, &self.client.pk, &self.server.u);
+    let client_key = self.client.compute_key(
+      &self.password,
+      &self.n,
+      &self.server.pk,
+      &self.server.salt,
+      &self.server.u,
+    );
+    assert_eq!(server_key, client_key);
+
+    // C sends the digest HMAC(K, salt) to S. The ODA simulator still uses HMAC-SHA1
+    let hmac = Sha1HMac::new(&client_key);
+    let client_digest: Sha1Digest = hmac.authenticate(&self.server.salt.to_bytes_be());
+
+    // S validates HMAC(K, salt)
+    let hmac = Sha1HMac::new(&server_key);
+    hmac.verify(&self.server.salt.to_bytes_be(), client_digest)
+  }
+
+  pub fn mitm_crack_password(&self, dictionary: &[String]) -> Option<String> {
+    let salt = BigUint::zero();
+    // u = 1 → s = B^a * B^x = A^b * B^x (because of diffie_hellman), and A^b does not depend on the password
+    let shared = mod_exp(&self.client.pk, &self.server.sk, &self.n);
+
+    // The dictionary means that M is able to find the word if it is common
+    dictionary
+      .iter()
+      .find(|possible_password| {
+        let x = salt_then_hash_biguint(&salt, possible_password);
+        let s = (&shared * mod_exp(&self.server.pk, &x, &self.n)) % &self.n;
+        let mut hasher = Sha256::new();
+        hasher.update(s.to_bytes_be());
+        let possible_key = hasher.finalize().to_vec();
+        Sha1HMac::new(&possible_key).verify(&salt.to_bytes_be(), self.client_digest)
+      })
+      .cloned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_srp_oda() {
+    let email = String::from("lorenzo@gmail.com");
+    let password = String::from("abcdefghijklm");
+    let srp = SrpSimulatorODA::for_email_password(&email, &password);
+    assert!(srp.validate());
+  }
+
+  #[test]
+  fn test_mitm_crack_password() {
+    let email = String::from("lorenzo@gmail.com");
+    let dictionary: Vec<String> = ["HOLAQUETAL", "BOCAJUNIORS", "ParalelePIPEDO", "MCLPLODLK"]
+      .iter()
+      .map(|word| word.to_string())
+      .collect();
+    let srp = SrpSimulatorODA::for_email_password(&email, &dictionary[2]);
+    assert_eq!(srp.mitm_crack_password(&dictionary), Some(dictionary[2].clone()));
+    assert_eq!(srp.mitm_crack_password(&dictionary[..2]), None);
+  }
+}
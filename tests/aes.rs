@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
   use cryptopals::utils::{
-    aes::{aes::AES, aes_block::AESBlock, constants::*, utils::AESMode},
+    aes::{aes::AES, aes_block::AESBlock, aes_error::AESError, aes_key::AESKey, constants::*, stream_cipher::AesStreamCipher, utils::{AESMode, CtrConfig}},
     conversion::hex_string::HexString,
   };
 
@@ -35,7 +35,7 @@ mod tests {
     );
 
     let keys = aes.compute_all_round_keys();
-    let hexs = keys.map(|k| k.as_hex_string());
+    let hexs: Vec<HexString> = keys.iter().map(|k| k.as_hex_string()).collect();
     assert_eq!(
       hexs,
       [
@@ -53,6 +53,49 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_expand_key_fips_197_vectors() {
+    // (key, second round key, last round key) from FIPS-197 Appendix A
+    let vectors = [
+      (
+        "2b7e151628aed2a6abf7158809cf4f3c",
+        "a0fafe1788542cb123a339392a6c7605",
+        "d014f9a8c9ee2589e13f0cc8b6630ca6",
+        AES128_ROUNDS,
+      ),
+      (
+        "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+        "62f8ead2522c6b7bfe0c91f72402f5a5",
+        "e98ba06f448c773c8ecc720401002202",
+        AES192_ROUNDS,
+      ),
+      (
+        "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+        "1f352c073b6108d72d9810a30914dff4",
+        "fe4890d1e6188d0b046df344706c631e",
+        AES256_ROUNDS,
+      ),
+    ];
+    for (key, second, last, rounds) in vectors {
+      let key_bytes = HexString::try_from(key).unwrap().as_vector_of_bytes().unwrap();
+      let key = AESKey::from_bytes(&key_bytes).unwrap();
+      let round_keys = key.expand_key();
+      assert_eq!(round_keys.len(), rounds + 1);
+      assert_eq!(round_keys[0].as_flatten_array(), key_bytes[..16]);
+      assert_eq!(round_keys[1].as_hex_string(), HexString::try_from(second).unwrap());
+      assert_eq!(round_keys[rounds].as_hex_string(), HexString::try_from(last).unwrap());
+    }
+  }
+
+  #[test]
+  fn test_expand_key_matches_compute_all_round_keys() {
+    let aes = AES::create_from(b"Thats my Kung Fu", AESMode::ECB).unwrap();
+    let expanded = aes.key.expand_key();
+    for (round_key, block) in aes.compute_all_round_keys().iter().zip(&expanded[1..]) {
+      assert_eq!(round_key.as_hex_string(), block.as_hex_string());
+    }
+  }
+
   #[test]
   fn test_aes_add_round_key() {
     let plaintext = b"Two One Nine Two";
@@ -100,19 +143,121 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_aes_128_one_round_without_mutation() {
+    let aes = AES::create_from(b"Thats my Kung Fu", AESMode::ECB).unwrap();
+    let keys = aes.compute_all_round_keys();
+    let block = AES::divide_in_blocks(b"Two One Nine Two").unwrap()[0].with_add_round_key(&aes.key);
+
+    let subbed_bytes = block.with_sub_bytes();
+    let shifted_rows = subbed_bytes.with_shift_rows();
+    let mixed_columns = shifted_rows.with_mix_columns();
+    let result = mixed_columns.with_add_round_key(&keys[0]);
+    // Each state is still the one of its step after the following ones were computed
+    let expected = [
+      (block, "001f0e543c4e08596e221b0b4774311a"),
+      (subbed_bytes, "63c0ab20eb2f30cb9f93af2ba092c7a2"),
+      (shifted_rows, "632fafa2eb93c7209f92abcba0c0302b"),
+      (mixed_columns, "ba75f47a84a48d32e88d060e1b407d5d"),
+      (result, "5847088b15b61cba59d4e2e8cd39dfce"),
+    ];
+    for (state, hex) in expected {
+      assert_eq!(state.as_hex_string(), HexString::try_from(hex).unwrap());
+    }
+
+    assert_eq!(mixed_columns.with_inv_mix_columns(), shifted_rows);
+    assert_eq!(shifted_rows.with_inv_shift_rows(), subbed_bytes);
+    assert_eq!(subbed_bytes.with_inv_sub_bytes(), block);
+  }
+
+  #[test]
+  fn test_encrypt_block_trace() {
+    let aes = AES::create_from(b"Thats my Kung Fu", AESMode::ECB).unwrap();
+    let block = AES::divide_in_blocks(b"Two One Nine Two").unwrap()[0];
+    let trace = aes.encrypt_block_trace(&block);
+    // input, round 0, 9 rounds of 4 steps and a last round without mix_columns
+    assert_eq!(trace.len(), 2 + 9 * 4 + 3);
+    let expected_first_round = [
+      ("round 0 add_round_key", "001f0e543c4e08596e221b0b4774311a"),
+      ("round 1 sub_bytes", "63c0ab20eb2f30cb9f93af2ba092c7a2"),
+      ("round 1 shift_rows", "632fafa2eb93c7209f92abcba0c0302b"),
+      ("round 1 mix_columns", "ba75f47a84a48d32e88d060e1b407d5d"),
+      ("round 1 add_round_key", "5847088b15b61cba59d4e2e8cd39dfce"),
+    ];
+    for ((label, state), (expected_label, expected_hex)) in trace[1..6].iter().zip(expected_first_round) {
+      assert_eq!(label, expected_label);
+      assert_eq!(state.as_hex_string(), HexString::try_from(expected_hex).unwrap());
+    }
+    let (last_label, output) = trace.last().unwrap();
+    assert_eq!(last_label, "round 10 add_round_key");
+    assert_eq!(output.as_hex_string(), HexString::try_from("29c3505f571420f6402299b31a02d73a").unwrap());
+  }
+
   #[test]
   fn test_aes_128_ecb_encode() {
     let plaintext = b"Two One Nine TwoTwo One Nine Two";
     let initial_key = b"Thats my Kung Fu";
     let ciphertext = AES::encode(plaintext, initial_key, AESMode::ECB)
       .expect("An error occured during AES execution.");
+    // The plaintext is block aligned, so a whole block of padding follows
+    assert_eq!(ciphertext.len(), 48);
     assert_eq!(
-      HexString::try_from(ciphertext).unwrap(),
+      HexString::try_from(ciphertext[..32].to_vec()).unwrap(),
       HexString::try_from("29c3505f571420f6402299b31a02d73a29c3505f571420f6402299b31a02d73a")
         .unwrap()
     )
   }
 
+  #[test]
+  fn test_aes_ecb_encode_without_padding() {
+    let key = b"Thats my Kung Fu";
+    let ciphertext = AES::encode_ecb_nopad(b"Two One Nine Two", key).unwrap();
+    assert_eq!(ciphertext.len(), 16);
+    assert_eq!(
+      HexString::try_from(ciphertext).unwrap(),
+      HexString::try_from("29c3505f571420f6402299b31a02d73a").unwrap()
+    );
+    assert_eq!(AES::encode_ecb_nopad(b"Two One Nine Two!", key), Err(AESError::InvalidBlockSize(17)));
+    assert_eq!(AES::encode_ecb_nopad(b"", key), Err(AESError::InvalidBlockSize(0)));
+  }
+
+  #[test]
+  fn test_aes_fips_197_appendix_c_vectors() {
+    let plaintext = HexString::try_from("00112233445566778899aabbccddeeff").unwrap().as_vector_of_bytes().unwrap();
+    let vectors = [
+      ("000102030405060708090a0b0c0d0e0f", "69c4e0d86a7b0430d8cdb78070b4c55a"),
+      ("000102030405060708090a0b0c0d0e0f1011121314151617", "dda97ca4864cdfe06eaf70a0ec0d7191"),
+      (
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        "8ea2b7ca516745bfeafc49904b496089",
+      ),
+    ];
+    for (key, expected) in vectors {
+      let key = HexString::try_from(key).unwrap().as_vector_of_bytes().unwrap();
+      let ciphertext = AES::encode_ecb_nopad(&plaintext, &key).unwrap();
+      assert_eq!(HexString::try_from(ciphertext.clone()).unwrap(), HexString::try_from(expected).unwrap());
+      assert_eq!(AES::decode_no_unpad(&ciphertext, &key, AESMode::ECB).unwrap(), plaintext);
+    }
+  }
+
+  #[test]
+  fn test_aes_192_and_256_round_trips() {
+    let plaintext = b"AGUANTE EL CLUB ATLETICO Y RECREATIVO GENERAL SAN MARTIN DE LAS ESCOBAS!!!!!!!!!"; // Block aligned, so no padding is added
+    for key in [b"YELLOW SUBMARINE, YELLOW".to_vec(), b"YELLOW SUBMARINE YELLOW SUBMARIN".to_vec()] {
+      let aes = AES::create_from(&key, AESMode::ECB).unwrap();
+      assert_eq!(aes.get_round_keys().len(), aes.key.rounds() + 1);
+      let round_keys = aes.compute_all_round_keys();
+      assert_eq!(round_keys.len(), aes.key.rounds());
+      assert_eq!(round_keys[0].as_block(), aes.key.expand_key()[1]);
+      assert_eq!(aes.key.as_block().as_flatten_array(), key[..16]);
+      for mode in [AESMode::ECB, AESMode::CBC([7; 16]), AESMode::CTR(42)] {
+        let ciphertext = AES::encode(plaintext, &key, mode.clone()).unwrap();
+        assert_ne!(ciphertext, plaintext.to_vec());
+        assert_eq!(AES::decode(&ciphertext, &key, mode).unwrap(), plaintext);
+      }
+    }
+  }
+
   #[test]
   fn test_one_inverse_round() {
     let initial_key = b"Thats my Kung Fu".clone();
@@ -164,7 +309,7 @@ mod tests {
         .as_vector_of_bytes()
         .unwrap();
     let initial_key = b"Thats my Kung Fu";
-    let plaintext = AES::decode(&ciphertext, initial_key, AESMode::ECB)
+    let plaintext = AES::decode_no_unpad(&ciphertext, initial_key, AESMode::ECB)
       .expect("An error occured during AES execution.");
     assert_eq!(
       HexString::try_from(plaintext).unwrap(),
@@ -179,8 +324,9 @@ mod tests {
     let secret_key = b"YELLOW SUBMARINE";
     let iv = [0u8; 16];
     let ciphertext = AES::encode(plaintext, secret_key, AESMode::CBC(iv)).unwrap();
+    assert_eq!(ciphertext.len(), 48);
     assert_eq!(
-      HexString::try_from(ciphertext).unwrap(),
+      HexString::try_from(ciphertext[..32].to_vec()).unwrap(),
       HexString::try_from("B4AA1A676828A22B6D8326EC96C526194885CB8A2625DE254C4089C2961257F4")
         .unwrap()
     )
@@ -195,7 +341,7 @@ mod tests {
         .unwrap();
     let secret_key = b"YELLOW SUBMARINE";
     let iv = [0u8; 16];
-    let plaintext = AES::decode(&ciphertext, secret_key, AESMode::CBC(iv)).unwrap();
+    let plaintext = AES::decode_no_unpad(&ciphertext, secret_key, AESMode::CBC(iv)).unwrap();
     assert_eq!(
       HexString::try_from(plaintext).unwrap(),
       HexString::try_from("416775616E746520426F636161616161416775616E746520426F636161616161")
@@ -224,4 +370,201 @@ mod tests {
       b"BOCA YO TE AMO YO TE SIGO A TODOS LADOS DE CORAZON"
     );
   }
+
+  #[test]
+  fn test_aes_block_u128_round_trip() {
+    let block = AESBlock::from_flat_array(b"Two One Nine Two");
+    assert_eq!(AESBlock::from_u128(block.to_u128()), block);
+    assert_eq!(
+      AESBlock::from_u128(0x000102030405060708090a0b0c0d0e0f).as_flatten_array(),
+      [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+    );
+  }
+
+  #[test]
+  fn test_aes_block_u128_increment() {
+    let mut bytes = [0u8; 16];
+    bytes[12..].copy_from_slice(&[0x00, 0x00, 0xff, 0xff]);
+    let block = AESBlock::from_flat_array(&bytes);
+    let incremented = AESBlock::from_u128(block.to_u128() + 1);
+
+    // Big-endian increment at the byte level, propagating the carry
+    for byte in bytes.iter_mut().rev() {
+      let (value, overflow) = byte.overflowing_add(1);
+      *byte = value;
+      if !overflow {
+        break;
+      }
+    }
+    assert_eq!(incremented.as_flatten_array(), bytes);
+  }
+
+  #[test]
+  fn test_aes_gcm_nist_vectors() {
+    let bytes = |hex: &str| HexString::try_from(hex).unwrap().as_vector_of_bytes().unwrap();
+    let key = "feffe9928665731c6d6a8f9467308308";
+    let nonce = "cafebabefacedbaddecaf888";
+    let plaintext = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                     1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+    let aad = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    // (key, nonce, plaintext, aad, ciphertext, tag) of test cases 1, 2, 3, 4 and 16 of the GCM specification
+    let vectors = [
+      ("00000000000000000000000000000000", "000000000000000000000000", "", "", "",
+       "58e2fccefa7e3061367f1d57a4e7455a"),
+      ("00000000000000000000000000000000", "000000000000000000000000", "00000000000000000000000000000000", "",
+       "0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf"),
+      (key, nonce, plaintext, "",
+       "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+        21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+       "4d5c2af327cd64a62cf35abd2ba6fab4"),
+      (key, nonce, &plaintext[..120], aad,
+       "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+        21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+       "5bc94fbc3221a5db94fae95ae7121a47"),
+      ("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308", nonce, &plaintext[..120], aad,
+       "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+        8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+       "76fc6ece0f4e1768cddf8853bb2d551b"),
+    ];
+    for (key, nonce, plaintext, aad, ciphertext, tag) in vectors {
+      let mode = AESMode::GCM { nonce: bytes(nonce).try_into().unwrap(), aad: bytes(aad) };
+      let expected = [bytes(ciphertext), bytes(tag)].concat();
+      let encrypted = AES::encode(&bytes(plaintext), &bytes(key), mode.clone()).unwrap();
+      assert_eq!(encrypted, expected);
+      assert_eq!(AES::decode(&encrypted, &bytes(key), mode).unwrap(), bytes(plaintext));
+    }
+  }
+
+  #[test]
+  fn test_aes_gcm_rejects_tampering() {
+    let key = b"YELLOW SUBMARINE";
+    let mode = AESMode::GCM { nonce: [7; 12], aad: b"header".to_vec() };
+    let ciphertext = AES::encode(b"attack at dawn", key, mode.clone()).unwrap();
+    assert_eq!(ciphertext.len(), 14 + 16);
+
+    for i in 0..ciphertext.len() {
+      let mut tampered = ciphertext.clone();
+      tampered[i] ^= 1;
+      assert_eq!(AES::decode(&tampered, key, mode.clone()), Err(AESError::AuthenticationFailed));
+    }
+    let other_aad = AESMode::GCM { nonce: [7; 12], aad: b"HEADER".to_vec() };
+    assert_eq!(AES::decode(&ciphertext, key, other_aad), Err(AESError::AuthenticationFailed));
+    assert_eq!(AES::decode(&ciphertext[..15].to_vec(), key, mode), Err(AESError::InvalidBlockSize(15)));
+  }
+
+  #[test]
+  fn test_aes_decode_strips_padding() {
+    let key = b"YELLOW SUBMARINE";
+    // The last byte looks like a whole block of padding, but it is part of the message
+    let plaintext = b"YELLOW SUBMARIN\x10";
+    for mode in [AESMode::ECB, AESMode::CBC([3; 16])] {
+      let ciphertext = AES::encode(plaintext, key, mode.clone()).unwrap();
+      assert_eq!(ciphertext.len(), 32);
+      assert_eq!(AES::decode(&ciphertext, key, mode.clone()).unwrap(), plaintext);
+      let padded = AES::decode_no_unpad(&ciphertext, key, mode).unwrap();
+      assert_eq!(padded, [plaintext.as_slice(), &[16; 16]].concat());
+    }
+    let ciphertext = AES::encode(b"ICE ICE BABY", key, AESMode::CBC([0; 16])).unwrap();
+    assert_eq!(AES::decode(&ciphertext, key, AESMode::CBC([0; 16])).unwrap(), b"ICE ICE BABY");
+  }
+
+  #[test]
+  fn test_aes_decode_rejects_invalid_padding() {
+    let key = b"YELLOW SUBMARINE";
+    let mode = AESMode::CBC([0; 16]);
+    let mut ciphertext = AES::encode(b"ICE ICE BABY", key, mode.clone()).unwrap();
+    // Flipping a bit of the IV flips the same bit of the first (and only) plaintext block
+    let mut iv = [0; 16];
+    iv[15] = 0x01;
+    let tampered_iv = AESMode::CBC(iv);
+    assert_eq!(AES::decode(&ciphertext, key, tampered_iv.clone()), Err(AESError::PaddingError));
+    let plaintext = AES::decode_no_unpad(&ciphertext, key, tampered_iv).unwrap();
+    assert_eq!(plaintext, b"ICE ICE BABY\x04\x04\x04\x05");
+
+    ciphertext.pop();
+    assert_eq!(AES::decode(&ciphertext, key, mode), Err(AESError::InvalidBlockSize(15)));
+  }
+
+  #[test]
+  fn test_aes_cfb_and_ofb_round_trips() {
+    let key = b"YELLOW SUBMARINE";
+    let iv = [9u8; 16];
+    for length in [1, 15, 16, 17, 40, 48] {
+      let plaintext: Vec<u8> = (0..length as u8).collect();
+      for mode in [AESMode::CFB(iv), AESMode::OFB(iv)] {
+        let ciphertext = AES::encode(&plaintext, key, mode.clone()).unwrap();
+        // Stream modes do not pad
+        assert_eq!(ciphertext.len(), length);
+        assert_eq!(AES::decode(&ciphertext, key, mode).unwrap(), plaintext);
+      }
+    }
+  }
+
+  #[test]
+  fn test_aes_cfb_and_ofb_known_answers() {
+    // NIST SP 800-38A, F.2.1, F.3.13 and F.4.1 (first two blocks)
+    let bytes = |hex: &str| HexString::try_from(hex).unwrap().as_vector_of_bytes().unwrap();
+    let key = bytes("2b7e151628aed2a6abf7158809cf4f3c");
+    let iv: [u8; 16] = bytes("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+    let plaintext = bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+    let cbc = AES::encode(&plaintext, &key, AESMode::CBC(iv)).unwrap();
+    let cfb = AES::encode(&plaintext, &key, AESMode::CFB(iv)).unwrap();
+    let ofb = AES::encode(&plaintext, &key, AESMode::OFB(iv)).unwrap();
+    assert_eq!(cbc[..32], bytes("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2"));
+    assert_eq!(cfb, bytes("3b3fd92eb72dad20333449f8e83cfb4ac8a64537a0b3a93fcde3cdad9f1ce58b"));
+    assert_eq!(ofb, bytes("3b3fd92eb72dad20333449f8e83cfb4a7789508d16918f03f53c52dac54ed825"));
+    // The first block is E(k, IV) ^ P1 in both stream modes, but not in CBC
+    assert_ne!(cfb, cbc[..32]);
+    assert_ne!(ofb, cbc[..32]);
+    assert_ne!(cfb[16..], ofb[16..]);
+  }
+
+  #[test]
+  fn test_aes_ctr_nist_vectors() {
+    // NIST SP 800-38A, F.5.1: the initial counter block f0f1...feff is a 96-bit nonce and a 32-bit big-endian counter
+    let bytes = |hex: &str| HexString::try_from(hex).unwrap().as_vector_of_bytes().unwrap();
+    let key = bytes("2b7e151628aed2a6abf7158809cf4f3c");
+    let mode = AESMode::CTRWithConfig { nonce: bytes("f0f1f2f3f4f5f6f7f8f9fafb"), config: CtrConfig::nist(0xfcfdfeff) };
+    let plaintext = bytes(
+      "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+       30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    );
+    let ciphertext = AES::encode(&plaintext, &key, mode.clone()).unwrap();
+    assert_eq!(
+      ciphertext,
+      bytes(
+        "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+         5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+      )
+    );
+    assert_eq!(AES::decode(&ciphertext, &key, mode).unwrap(), plaintext);
+  }
+
+  #[test]
+  fn test_aes_ctr_default_config_matches_ctr() {
+    let key = b"YELLOW SUBMARINE";
+    let plaintext = b"Ice, Ice, baby, the counter layout did not change";
+    let mode = AESMode::CTRWithConfig { nonce: 7u64.to_le_bytes().to_vec(), config: CtrConfig::default() };
+    assert_eq!(
+      AES::encode(plaintext, key, mode).unwrap(),
+      AES::encode(plaintext, key, AESMode::CTR(7)).unwrap()
+    );
+    // Counter first, and a 16-bit counter that wraps around
+    let config = CtrConfig { counter_bytes: 2, big_endian: true, nonce_first: false, initial_counter: 0xffff };
+    assert_eq!(config.counter_block(&[0xaa; 14], 0)[..3], [0xff, 0xff, 0xaa]);
+    assert_eq!(config.counter_block(&[0xaa; 14], 1)[..3], [0x00, 0x00, 0xaa]);
+    let mode = AESMode::CTRWithConfig { nonce: vec![0; 12], config: CtrConfig::default() };
+    assert_eq!(AES::encode(plaintext, key, mode), Err(AESError::InvalidNonceSize(12, 8)));
+  }
+
+  #[test]
+  fn test_aes_ctr_invalid_counter_size() {
+    let key = b"YELLOW SUBMARINE";
+    for counter_bytes in [0, 9, 17] {
+      let config = CtrConfig { counter_bytes, ..CtrConfig::default() };
+      let mode = AESMode::CTRWithConfig { nonce: vec![0; config.nonce_len()], config };
+      assert_eq!(AES::encode(b"plaintext", key, mode.clone()), Err(AESError::InvalidCounterSize(counter_bytes)));
+      assert_eq!(AesStreamCipher::new(key, mode).err(), Some(AESError::InvalidCounterSize(counter_bytes)));
+    }
+  }
 }